@@ -0,0 +1,27 @@
+#![no_main]
+
+/**
+ * cpu.rs
+ *
+ * Runs random instruction streams.
+**/
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate oxidgb_core_fuzz;
+
+use oxidgb_core_fuzz::build_system;
+use oxidgb_core_fuzz::checked_tick;
+
+/// Enough to run a few frames, without slowing the fuzzer down too much.
+const MAX_STEPS : usize = 100000;
+
+fuzz_target!(|data : &[u8]| {
+    let mut cpu = build_system(data);
+
+    for _ in 0 .. MAX_STEPS {
+        if !checked_tick(&mut cpu) {
+            break;
+        }
+    }
+});
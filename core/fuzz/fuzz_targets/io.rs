@@ -0,0 +1,43 @@
+#![no_main]
+
+/**
+ * io.rs
+ *
+ * Runs random sequences of I/O register accesses, interleaved with execution.
+**/
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate oxidgb_core_fuzz;
+
+use oxidgb_core_fuzz::build_system;
+use oxidgb_core_fuzz::checked_tick;
+
+fuzz_target!(|data : &[u8]| {
+    // The CPU runs through a NOP sled, while registers are poked underneath it
+    let mut cpu = build_system(&[]);
+
+    // Each access is: operation, register, value
+    for access in data.chunks(3) {
+        if access.len() < 3 {
+            break;
+        }
+
+        let address = 0xFF00 | access[1] as u16;
+
+        match access[0] % 3 {
+            0 => {
+                cpu.mem.read(address);
+            },
+            1 => cpu.mem.write(address, access[2]),
+            _ => {
+                // Let time pass, so registers take effect
+                for _ in 0 .. access[2] {
+                    if !checked_tick(&mut cpu) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+});
@@ -0,0 +1,42 @@
+#![no_main]
+
+/**
+ * rom.rs
+ *
+ * Parses random cartridge headers, and reads across any that are accepted.
+**/
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate oxidgb_core;
+
+use oxidgb_core::events::EventQueue;
+use oxidgb_core::rom::GameROM;
+
+fuzz_target!(|data : &[u8]| {
+    let mut rom = match GameROM::try_build(data.to_vec()) {
+        Ok(rom) => rom,
+        Err(_) => return
+    };
+
+    rom.header_checksum();
+    rom.global_checksum();
+    rom.supports_cgb();
+
+    let events = EventQueue::default();
+
+    // Switch through every bank, sampling across it
+    for bank in 0 .. 0x80u8 {
+        rom.write(0x2100, bank, &events);
+
+        for ptr in (0x0000 .. 0x8000u16).step_by(0x80) {
+            rom.read(ptr);
+            rom.read(ptr + 0x7F);
+        }
+    }
+
+    for ptr in 0x0000 .. 0x2000u16 {
+        rom.write_ram(ptr, ptr as u8);
+        rom.read_ram(ptr);
+    }
+});
@@ -0,0 +1,75 @@
+/**
+ * lib.rs
+ *
+ * Shared setup for the fuzz targets.
+**/
+
+extern crate oxidgb_core;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::mem::GBMemory;
+use oxidgb_core::rom::GameROM;
+
+/// The most cycles a single tick may take: 24 for the longest instruction, or
+///  64 while halted/stopped.
+pub const MAX_STEP_CYCLES : u32 = 64;
+
+/// Where fuzzed programs are placed, just after the cartridge header.
+const PROGRAM_START : usize = 0x150;
+
+const ROM_SIZE : usize = 0x8000;
+
+/// Returns if an opcode is one which locks up real hardware. The core panics on
+///  these deliberately, so they aren't interesting to fuzz.
+pub fn is_illegal(opcode : u8) -> bool {
+    match opcode {
+        0xD3 | 0xDB | 0xDD | 0xE3 | 0xE4 | 0xEB | 0xEC | 0xED | 0xF4 | 0xFC | 0xFD => true,
+        _ => false
+    }
+}
+
+/// Builds a system around a 32KB ROM-only cartridge, which jumps straight into
+///  the specified program.
+pub fn build_system(program : &[u8]) -> CPU {
+    let mut data = vec![0u8; ROM_SIZE];
+
+    // JP $0150
+    data[0x100 .. 0x103].copy_from_slice(&[0xC3, PROGRAM_START as u8, (PROGRAM_START >> 8) as u8]);
+
+    // ROM only, 32KB, no RAM
+    data[0x147] = 0x00;
+    data[0x148] = 0x00;
+    data[0x149] = 0x00;
+
+    let length = program.len().min(ROM_SIZE - PROGRAM_START);
+    for (target, &opcode) in data[PROGRAM_START ..].iter_mut().zip(&program[.. length]) {
+        *target = if is_illegal(opcode) { 0x00 } else { opcode };
+    }
+
+    let rom = GameROM::try_build(data).expect("fuzz ROM should always be valid");
+    CPU::build(GBMemory::build(rom))
+}
+
+/// Runs a single instruction, checking that it took a sensible amount of time.
+///  Returns false if the next instruction is illegal, and execution should stop.
+pub fn checked_tick(cpu : &mut CPU) -> bool {
+    let pc = cpu.regs.pc;
+
+    if is_illegal(cpu.mem.read(pc)) {
+        return false;
+    }
+
+    let before = cpu.cycle_counter;
+    cpu.tick(&mut None);
+    let cycles = cpu.cycle_counter.wrapping_sub(before);
+
+    assert!(cycles <= MAX_STEP_CYCLES, "Tick at ${:04X} took {} cycles", pc, cycles);
+
+    // Drain audio, as a frontend would
+    if cpu.cycle_counter > 70224 {
+        cpu.cycle_counter = 0;
+        cpu.mem.sound.take_samples();
+    }
+
+    true
+}
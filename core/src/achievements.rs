@@ -0,0 +1,106 @@
+/**
+ * achievements.rs
+ *
+ * A flat view of memory laid out as RetroAchievements expects for the Game Boy,
+ *  so achievement runtimes (such as rcheevos) can read it without knowing how
+ *  the core stores it.
+**/
+
+use mem::GBMemory;
+
+/// The size of the flat address space. Addresses past 0xFFFF hold memory which
+///  is banked out of the CPU's view.
+pub const MEMORY_SIZE : u32 = 0x1E000;
+
+/// How a region of the flat address space is used.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RegionKind {
+    ReadOnly,
+    Hardware,
+    VideoRam,
+    SaveRam,
+    SystemRam,
+    /// Mirrors another region.
+    Mirror,
+    /// Nothing is mapped here on this system.
+    Unused
+}
+
+/// A region of the flat address space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MemoryRegion {
+    pub start : u32,
+    /// The last address in the region, inclusive.
+    pub end : u32,
+    pub kind : RegionKind,
+    pub name : &'static str
+}
+
+macro_rules! region {
+    ($start:expr, $end:expr, $kind:ident, $name:expr) => {
+        MemoryRegion { start : $start, end : $end, kind : RegionKind::$kind, name : $name }
+    }
+}
+
+/// The regions of the flat address space, matching rcheevos' Game Boy map. The
+///  Game Boy Color's extra work RAM banks have their space reserved, but unused.
+pub const REGIONS : [MemoryRegion; 18] = [
+    region!(0x00000, 0x000FF, Hardware, "Interrupt vector"),
+    region!(0x00100, 0x0014F, ReadOnly, "Cartridge header"),
+    region!(0x00150, 0x03FFF, ReadOnly, "Cartridge ROM (fixed)"),
+    region!(0x04000, 0x07FFF, ReadOnly, "Cartridge ROM (paged)"),
+    region!(0x08000, 0x097FF, VideoRam, "Tile RAM"),
+    region!(0x09800, 0x09BFF, VideoRam, "BG1 map data"),
+    region!(0x09C00, 0x09FFF, VideoRam, "BG2 map data"),
+    region!(0x0A000, 0x0BFFF, SaveRam, "Cartridge RAM"),
+    region!(0x0C000, 0x0CFFF, SystemRam, "System RAM (fixed)"),
+    region!(0x0D000, 0x0DFFF, SystemRam, "System RAM (bank 1)"),
+    region!(0x0E000, 0x0FDFF, Mirror, "Echo RAM"),
+    region!(0x0FE00, 0x0FE9F, VideoRam, "Sprite RAM"),
+    region!(0x0FEA0, 0x0FEFF, Unused, "Unused"),
+    region!(0x0FF00, 0x0FF7F, Hardware, "Hardware I/O"),
+    region!(0x0FF80, 0x0FFFE, SystemRam, "Quick RAM"),
+    region!(0x0FFFF, 0x0FFFF, Hardware, "Interrupt enable"),
+    region!(0x10000, 0x15FFF, Unused, "Unused (GameBoy Color exclusive)"),
+    region!(0x16000, 0x1DFFF, SaveRam, "Cartridge RAM (banks 1-3)")
+];
+
+/// Where the extra cartridge RAM banks start in the flat address space.
+const EXTRA_CART_RAM_START : u32 = 0x16000;
+
+/// The size of a cartridge RAM bank.
+const CART_RAM_BANK_SIZE : usize = 0x2000;
+
+/// Reads a byte from the flat address space. Reads have no side effects, and
+///  ignore restrictions the PPU places on VRAM/OAM. Memory which doesn't exist
+///  on this system reads as 0.
+pub fn read(mem : &GBMemory, address : u32) -> u8 {
+    return match address {
+        // Read cart RAM directly, as mappers complain about missing RAM
+        0xA000 ... 0xBFFF => {
+            mem.rom.cart_ram.get((address - 0xA000) as usize).cloned().unwrap_or(0)
+        }
+        0x0000 ... 0xFFFF => mem.debug_read(address as u16),
+        0x10000 ... 0x15FFF => {
+            // Only the Game Boy Color has these work RAM banks
+            0
+        }
+        0x16000 ... 0x1DFFF => {
+            let offset = CART_RAM_BANK_SIZE + (address - EXTRA_CART_RAM_START) as usize;
+            mem.rom.cart_ram.get(offset).cloned().unwrap_or(0)
+        }
+        _ => 0
+    }
+}
+
+/// Reads a little-endian value of 1 to 4 bytes from the flat address space, as
+///  rcheevos' peek callback does.
+pub fn peek(mem : &GBMemory, address : u32, bytes : u32) -> u32 {
+    let mut value = 0;
+
+    for i in 0 .. bytes.min(4) {
+        value |= (read(mem, address.wrapping_add(i)) as u32) << (i * 8);
+    }
+
+    return value;
+}
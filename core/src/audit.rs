@@ -0,0 +1,75 @@
+/**
+ * audit.rs
+ *
+ * Hashes the state of each component of a system, so two runs which should be
+ *  identical (such as replays, or both sides of a netplay session) can be checked
+ *  for the first point where they diverge.
+**/
+
+use serde::Serialize;
+
+use cpu::CPU;
+
+use encode::encode;
+use encode::encode_without;
+use encode::Fnv1a;
+
+/// The components which are hashed separately, in the order they are stored.
+pub const COMPONENTS : [&'static str; 6] = ["CPU", "Work RAM", "Video", "Audio", "Cartridge", "I/O"];
+
+/// Hashes of each component of a system's state. Only state kept in save
+///  states is covered - debugging aids such as hooks and cheats are not.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StateHash {
+    pub components : [u64; 6]
+}
+
+impl StateHash {
+    /// Returns a single hash covering every component.
+    pub fn combined(&self) -> u64 {
+        hash(&self.components)
+    }
+
+    /// Returns the name of the first component which differs between two
+    ///  hashes, or None if they match.
+    pub fn first_difference(&self, other : &StateHash) -> Option<&'static str> {
+        for i in 0 .. COMPONENTS.len() {
+            if self.components[i] != other.components[i] {
+                return Some(COMPONENTS[i]);
+            }
+        }
+
+        return None;
+    }
+}
+
+fn hash<T : ?Sized + Serialize>(value : &T) -> u64 {
+    let mut hasher = Fnv1a::default();
+    encode(value, &mut hasher);
+    hasher.0
+}
+
+/// Hashes a struct, leaving out one of its fields.
+fn hash_without<T : ?Sized + Serialize>(value : &T, field : &'static str) -> u64 {
+    let mut hasher = Fnv1a::default();
+    encode_without(value, field, &mut hasher);
+    hasher.0
+}
+
+/// Hashes the state of a system.
+pub fn hash_state(cpu : &CPU) -> StateHash {
+    let mem = &cpu.mem;
+
+    StateHash {
+        components : [
+            // Everything but memory, which is split into the components below
+            hash_without(cpu, "mem"),
+            hash(&(&mem.ram, &mem.high_ram)),
+            hash(&mem.gpu),
+            hash(&mem.sound),
+            // The ROM itself can't change, so only the mapper's state is hashed
+            hash(&(mem.rom.current_bank(), &mem.rom.cart_ram)),
+            hash(&(&mem.ioregs, mem.interrupt_reg, mem.dirty_interrupts, &mem.buttons))
+        ]
+    }
+}
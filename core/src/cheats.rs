@@ -0,0 +1,244 @@
+/**
+ * cheats.rs
+ *
+ * Parses and applies cheat codes. Game Genie codes patch reads from the
+ *  cartridge ROM, while GameShark codes write to RAM once per frame.
+**/
+
+use alloc::String;
+use alloc::Vec;
+
+use core::mem::size_of;
+
+/// Reasons why a cheat code could not be parsed.
+#[derive(PartialEq, Debug)]
+pub enum CheatError {
+    /// The code doesn't have a length of any known format.
+    InvalidLength(usize),
+    InvalidDigit(char),
+    /// The code would patch an address outside of the memory it can affect.
+    InvalidAddress(u16),
+    /// A GameShark code type which isn't supported, such as CGB WRAM banks.
+    UnsupportedType(u8)
+}
+
+/// What a cheat code does.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum CheatEffect {
+    /// Replaces a byte read from ROM. With a compare byte, only reads which
+    ///  would otherwise return it are replaced - this is how the correct ROM
+    ///  bank is picked out.
+    RomPatch {
+        address : u16,
+        value : u8,
+        compare : Option<u8>
+    },
+    /// Writes a byte to RAM at the start of every VBlank, overriding whatever
+    ///  the game put there.
+    RamWrite {
+        address : u16,
+        value : u8
+    }
+}
+
+/// A parsed cheat code, and if it should currently be applied.
+#[derive(Debug, Clone)]
+pub struct Cheat {
+    /// The code as it was entered.
+    pub code : String,
+    pub effect : CheatEffect,
+    pub enabled : bool
+}
+
+fn hex_digit(digit : char) -> Result<u8, CheatError> {
+    match digit.to_digit(16) {
+        Some(value) => Ok(value as u8),
+        None => Err(CheatError::InvalidDigit(digit))
+    }
+}
+
+/// Parses a Game Genie code, in the form "ABC-DEF" or "ABC-DEF-GHI":
+///  * AB is the new value.
+///  * FCDE is the address, with F inverted.
+///  * GI is the compare byte, XORed with $BA then rotated left by 2. H is unused.
+pub fn parse_game_genie(code : &str) -> Result<CheatEffect, CheatError> {
+    let digits : Vec<char> = code.chars().filter(|c| *c != '-' && !c.is_whitespace()).collect();
+
+    if digits.len() != 6 && digits.len() != 9 {
+        return Err(CheatError::InvalidLength(digits.len()));
+    }
+
+    let mut values = Vec::with_capacity(digits.len());
+    for digit in &digits {
+        values.push(hex_digit(*digit)?);
+    }
+
+    let value = (values[0] << 4) | values[1];
+    let address = (((values[5] ^ 0xF) as u16) << 12) | ((values[2] as u16) << 8)
+        | ((values[3] as u16) << 4) | (values[4] as u16);
+
+    if address >= 0x8000 {
+        return Err(CheatError::InvalidAddress(address));
+    }
+
+    let compare = if values.len() == 9 {
+        let encoded = (values[6] << 4) | values[8];
+        Some(encoded.rotate_right(2) ^ 0xBA)
+    } else {
+        None
+    };
+
+    return Ok(CheatEffect::RomPatch {
+        address,
+        value,
+        compare
+    });
+}
+
+/// Parses a GameShark code, in the form "TTVVLLHH":
+///  * TT is the code type - 01 (and the less common 00) write to RAM.
+///  * VV is the value to write.
+///  * HHLL is the address, in cartridge RAM, work RAM or high RAM.
+pub fn parse_game_shark(code : &str) -> Result<CheatEffect, CheatError> {
+    let digits : Vec<char> = code.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if digits.len() != 8 {
+        return Err(CheatError::InvalidLength(digits.len()));
+    }
+
+    let mut bytes = [0u8; 4];
+    for (i, pair) in digits.chunks(2).enumerate() {
+        bytes[i] = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+    }
+
+    let code_type = bytes[0];
+    let value = bytes[1];
+    let address = ((bytes[3] as u16) << 8) | (bytes[2] as u16);
+
+    if code_type != 0x00 && code_type != 0x01 {
+        return Err(CheatError::UnsupportedType(code_type));
+    }
+
+    match address {
+        0xA000 ... 0xDFFF | 0xFF80 ... 0xFFFE => {},
+        _ => return Err(CheatError::InvalidAddress(address))
+    }
+
+    return Ok(CheatEffect::RamWrite {
+        address,
+        value
+    });
+}
+
+/// Parses a cheat code of any supported format. GameShark codes are 8 digits
+///  without dashes, while Game Genie codes are 6 or 9.
+pub fn parse(code : &str) -> Result<CheatEffect, CheatError> {
+    let code = code.trim();
+
+    if code.len() == 8 && !code.contains('-') {
+        return parse_game_shark(code);
+    }
+
+    return parse_game_genie(code);
+}
+
+/// Holds the cheats for the current game.
+#[derive(Default)]
+pub struct CheatManager {
+    cheats : Vec<Cheat>
+}
+
+impl CheatManager {
+    /// Parses and adds a cheat, returning its index.
+    pub fn add(&mut self, code : &str, enabled : bool) -> Result<usize, CheatError> {
+        let effect = parse(code)?;
+
+        self.cheats.push(Cheat {
+            code : String::from(code.trim()),
+            effect,
+            enabled
+        });
+
+        return Ok(self.cheats.len() - 1);
+    }
+
+    /// Replaces the cheat at a specific index, or adds it if the index is past
+    ///  the end. Used by frontends which number cheats themselves (such as libretro).
+    pub fn set(&mut self, index : usize, code : &str, enabled : bool) -> Result<(), CheatError> {
+        let cheat = Cheat {
+            code : String::from(code.trim()),
+            effect : parse(code)?,
+            enabled
+        };
+
+        if index < self.cheats.len() {
+            self.cheats[index] = cheat;
+        } else {
+            self.cheats.push(cheat);
+        }
+
+        return Ok(());
+    }
+
+    /// Enables or disables a cheat. Returns false if there is no such cheat.
+    pub fn set_enabled(&mut self, index : usize, enabled : bool) -> bool {
+        return match self.cheats.get_mut(index) {
+            Some(cheat) => {
+                cheat.enabled = enabled;
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn remove(&mut self, index : usize) -> Option<Cheat> {
+        if index < self.cheats.len() {
+            Some(self.cheats.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cheats.clear();
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        return &self.cheats;
+    }
+
+    /// Returns the RAM write for a cheat, if it is an enabled GameShark code.
+    pub fn ram_write(&self, index : usize) -> Option<(u16, u8)> {
+        return match self.cheats.get(index) {
+            Some(&Cheat { effect : CheatEffect::RamWrite { address, value }, enabled : true, .. }) =>
+                Some((address, value)),
+            _ => None
+        }
+    }
+
+    /// Returns how much heap memory the cheats use, in bytes.
+    pub fn heap_size(&self) -> usize {
+        self.cheats.capacity() * size_of::<Cheat>()
+            + self.cheats.iter().map(|cheat| cheat.code.capacity()).sum::<usize>()
+    }
+
+    /// Applies any enabled ROM patches to a value read from the cartridge.
+    pub fn patch_rom_read(&self, ptr : u16, value : u8) -> u8 {
+        for cheat in &self.cheats {
+            if !cheat.enabled {
+                continue;
+            }
+
+            match cheat.effect {
+                CheatEffect::RomPatch { address, value : patched, compare } => {
+                    if address == ptr && compare.map_or(true, |x| x == value) {
+                        return patched;
+                    }
+                },
+                CheatEffect::RamWrite { .. } => {}
+            }
+        }
+
+        return value;
+    }
+}
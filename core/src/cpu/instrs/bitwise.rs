@@ -41,7 +41,7 @@ pub fn xor_hl(cpu : &mut CPU) -> u8 {
  */
 pub fn xor_n(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
     reg_xor(&mut cpu.regs, value);
 
     return 8 /* Cycles */;
@@ -89,7 +89,7 @@ pub fn or_phl(cpu : &mut CPU) -> u8 {
  */
 pub fn or_n(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
     reg_or(&mut cpu.regs, value);
 
     return 8 /* Cycles */;
@@ -137,7 +137,7 @@ pub fn and_phl(cpu : &mut CPU) -> u8 {
  */
 pub fn and_n(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
     reg_and(&mut cpu.regs, value);
 
     return 8 /* Cycles */;
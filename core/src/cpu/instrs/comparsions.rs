@@ -38,7 +38,7 @@ pub fn cp_phl(cpu : &mut CPU) -> u8 {
 /// **0xFE** - *CP #* - Compare a with #
 pub fn cp_n(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
     let y = cpu.regs.a;
     compare_registers(&mut cpu.regs, y, value);
 
@@ -27,7 +27,7 @@ pub fn jr_n(cpu : &mut CPU) -> u8 {
 pub fn jr_nz_n(cpu : &mut CPU) -> u8 {
     if !cpu.regs.get_flag_z() {
         let n = get_n(cpu) as i8;
-        cpu.regs.pc = (cpu.regs.pc as i16 + n as i16) as u16;
+        cpu.regs.pc = cpu.regs.pc.wrapping_add(n as i16 as u16);
         return 12 /* Cycles */;
     } else {
         cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
@@ -148,13 +148,13 @@ pub fn jp_z_nn(cpu : &mut CPU) -> u8 {
 /// **0xCC** - *CALL Z,nn* - If Z is true jump to address nn and store current pc in stack
 pub fn call_z_nn(cpu : &mut CPU) -> u8 {
     if cpu.regs.get_flag_z() {
-        cpu.regs.sp -= 2;
-        cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc + 2);
+        cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
+        cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc.wrapping_add(2));
         cpu.regs.pc = cpu.mem.read_short(cpu.regs.pc);
         
         return 24 /* Cycles */;
     } else {
-        cpu.regs.pc += 2;
+        cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
         
         return 12 /* Cycles */;
     }
@@ -162,8 +162,8 @@ pub fn call_z_nn(cpu : &mut CPU) -> u8 {
 
 /// **0xCD** - *CALL nn* - Jump to address nn and store current pc in stack
 pub fn call_nn(cpu : &mut CPU) -> u8 {
-    cpu.regs.sp -= 2;
-    cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc + 2);
+    cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
+    cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc.wrapping_add(2));
     cpu.regs.pc = cpu.mem.read_short(cpu.regs.pc);
     
     return 24 /* Cycles */;
@@ -172,13 +172,13 @@ pub fn call_nn(cpu : &mut CPU) -> u8 {
 /// **0xD4** - *CALL NC,nn* - If N is false jump to address nn and store current pc in stack
 pub fn call_nc_nn(cpu : &mut CPU) -> u8 {
     if !cpu.regs.get_flag_c() {
-        cpu.regs.sp -= 2;
-        cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc + 2);
+        cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
+        cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc.wrapping_add(2));
         cpu.regs.pc = cpu.mem.read_short(cpu.regs.pc);
         
         return 24 /* Cycles */;
     } else {
-        cpu.regs.pc += 2;
+        cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
         
         return 12 /* Cycles */;
     }
@@ -187,13 +187,13 @@ pub fn call_nc_nn(cpu : &mut CPU) -> u8 {
 /// **0xDC** - *CALL C,nn* - If N is true jump to address nn and store current pc in stack
 pub fn call_c_nn(cpu : &mut CPU) -> u8 {
     if cpu.regs.get_flag_c() {
-        cpu.regs.sp -= 2;
-        cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc + 2);
+        cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
+        cpu.mem.write_short(cpu.regs.sp, cpu.regs.pc.wrapping_add(2));
         cpu.regs.pc = cpu.mem.read_short(cpu.regs.pc);
         
         return 24 /* Cycles */;
     } else {
-        cpu.regs.pc += 2;
+        cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
         
         return 12 /* Cycles */;
     }
@@ -205,7 +205,7 @@ pub fn call_c_nn(cpu : &mut CPU) -> u8 {
 pub fn ret_nz(cpu : &mut CPU) -> u8 {
     if !cpu.regs.get_flag_z() {
         cpu.regs.pc = cpu.mem.read_short(cpu.regs.sp);
-        cpu.regs.sp += 2;
+        cpu.regs.sp = cpu.regs.sp.wrapping_add(2);
         
         return 20 /* Cycles */;
     } else {
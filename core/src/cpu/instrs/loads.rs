@@ -18,7 +18,7 @@ pub fn ld_pxx_x(xx : u16, y : u8, cpu : &mut CPU) -> u8 {
 /// **0x06** - *LD b,#* - Put # in b
 pub fn ld_b_n(cpu : &mut CPU) -> u8 {
     cpu.regs.b = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
@@ -32,7 +32,7 @@ pub fn ld_n_pxx(mem : &GBMemory, xx : u16, n : &mut u8) -> u8 {
 /// **0x0E** - *LD c,#* - Put # in c
 pub fn ld_c_n(cpu : &mut CPU) -> u8 {
     cpu.regs.c = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
@@ -40,7 +40,7 @@ pub fn ld_c_n(cpu : &mut CPU) -> u8 {
 /// **0x16** - *LD d,#* - Put # in d
 pub fn ld_d_n(cpu : &mut CPU) -> u8 {
     cpu.regs.d = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
@@ -48,7 +48,7 @@ pub fn ld_d_n(cpu : &mut CPU) -> u8 {
 /// **0x1E** - *LD e,#* - Put # in e
 pub fn ld_e_n(cpu : &mut CPU) -> u8 {
     cpu.regs.e = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
@@ -65,7 +65,7 @@ pub fn ldi_phl_a(cpu : &mut CPU) -> u8 {
 /// **0x26** - *LD h,#* - Put # in h
 pub fn ld_h_n(cpu : &mut CPU) -> u8 {
     cpu.regs.h = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
@@ -73,7 +73,7 @@ pub fn ld_h_n(cpu : &mut CPU) -> u8 {
 /// **0x2A** - *LDI a,(hl)* - Put \*hl into a. Increment hl.
 pub fn ldi_a_phl(cpu : &mut CPU) -> u8 {
     cpu.regs.a = cpu.mem.read(cpu.regs.get_hl());
-    let new_value = cpu.regs.get_hl() + 1;
+    let new_value = cpu.regs.get_hl().wrapping_add(1);
     cpu.regs.set_hl(new_value);
 
     return 8 /* Cycles */;
@@ -82,7 +82,7 @@ pub fn ldi_a_phl(cpu : &mut CPU) -> u8 {
 /// **0x2E** - *LD l,#* - Put # in l
 pub fn ld_l_n(cpu : &mut CPU) -> u8 {
     cpu.regs.l = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
@@ -90,7 +90,7 @@ pub fn ld_l_n(cpu : &mut CPU) -> u8 {
 /// **0x32** - *LDD (hl),a* - Put a into \*hl. Decrement hl.
 pub fn ldd_phl_a(cpu : &mut CPU) -> u8 {
     cpu.mem.write(cpu.regs.get_hl(), cpu.regs.a);
-    let new_value = cpu.regs.get_hl() - 1;
+    let new_value = cpu.regs.get_hl().wrapping_sub(1);
     cpu.regs.set_hl(new_value);
 
     return 8 /* Cycles */;
@@ -100,7 +100,7 @@ pub fn ldd_phl_a(cpu : &mut CPU) -> u8 {
 pub fn ld_phl_n(cpu : &mut CPU) -> u8 {
     let new_value = cpu.mem.read(cpu.regs.pc);
     cpu.mem.write(cpu.regs.get_hl(), new_value);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 12 /* Cycles */;
 }
@@ -108,7 +108,7 @@ pub fn ld_phl_n(cpu : &mut CPU) -> u8 {
 /// **0x3A** - *LDD a,(hl)* - Put \*hl into a. Decrement hl.
 pub fn ldd_a_phl(cpu : &mut CPU) -> u8 {
     cpu.regs.a = cpu.mem.read(cpu.regs.get_hl());
-    let new_value = cpu.regs.get_hl() - 1;
+    let new_value = cpu.regs.get_hl().wrapping_sub(1);
     cpu.regs.set_hl(new_value);
 
     return 8 /* Cycles */;
@@ -117,7 +117,7 @@ pub fn ldd_a_phl(cpu : &mut CPU) -> u8 {
 /// **0x3E** - *LD l,#* - Put # in a
 pub fn ld_a_n(cpu : &mut CPU) -> u8 {
     cpu.regs.a = cpu.mem.read(cpu.regs.pc);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 8 /* Cycles */;
 }
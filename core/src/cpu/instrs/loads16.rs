@@ -12,7 +12,7 @@ use cpu::instrs::utils::*;
 pub fn ld_bc_nnnn(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.pc);
     cpu.regs.set_bc(value);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
@@ -21,7 +21,7 @@ pub fn ld_bc_nnnn(cpu : &mut CPU) -> u8 {
 pub fn ld_pnn_sp(cpu : &mut CPU) -> u8 {
     let pointer = cpu.mem.read_short(cpu.regs.pc);
     cpu.mem.write_short(pointer, cpu.regs.sp);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
 
     return 20 /* Cycles */;
 }
@@ -30,7 +30,7 @@ pub fn ld_pnn_sp(cpu : &mut CPU) -> u8 {
 pub fn ld_de_nn(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.pc);
     cpu.regs.set_de(value);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
@@ -39,7 +39,7 @@ pub fn ld_de_nn(cpu : &mut CPU) -> u8 {
 pub fn ld_hl_nnnn(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.pc);
     cpu.regs.set_hl(value);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
@@ -47,7 +47,7 @@ pub fn ld_hl_nnnn(cpu : &mut CPU) -> u8 {
 /// **0x31** - *LD sp,nn* - Put nn in sp
 pub fn ld_sp_nn(cpu : &mut CPU) -> u8 {
     cpu.regs.sp = cpu.mem.read_short(cpu.regs.pc);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
@@ -56,14 +56,14 @@ pub fn ld_sp_nn(cpu : &mut CPU) -> u8 {
 pub fn pop_bc(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.sp);
     cpu.regs.set_bc(value);
-    cpu.regs.sp += 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
 
 /// **0xC5** - *PUSH bc* - Push bc onto the stack
 pub fn push_bc(cpu : &mut CPU) -> u8 {
-    cpu.regs.sp -= 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
     cpu.mem.write_short(cpu.regs.sp, cpu.regs.get_bc());
 
     return 16 /* Cycles */;
@@ -73,14 +73,14 @@ pub fn push_bc(cpu : &mut CPU) -> u8 {
 pub fn pop_de(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.sp);
     cpu.regs.set_de(value);
-    cpu.regs.sp += 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
 
 /// **0xD5** - *PUSH de* - Push de onto the stack
 pub fn push_de(cpu : &mut CPU) -> u8 {
-    cpu.regs.sp -= 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
     cpu.mem.write_short(cpu.regs.sp, cpu.regs.get_de());
 
     return 16 /* Cycles */;
@@ -89,7 +89,7 @@ pub fn push_de(cpu : &mut CPU) -> u8 {
 /// **0xE0** - *LDH (n),a* - Put a in memory address *($FF00+n)
 pub fn ldh_pn_a(cpu : &mut CPU) -> u8 {
     let value = 0xFF00 + (cpu.mem.read(cpu.regs.pc) as u16);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
     cpu.mem.write(value, cpu.regs.a);
 
     return 12 /* Cycles */;
@@ -99,7 +99,7 @@ pub fn ldh_pn_a(cpu : &mut CPU) -> u8 {
 pub fn pop_hl(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.sp);
     cpu.regs.set_hl(value);
-    cpu.regs.sp += 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
@@ -114,7 +114,7 @@ pub fn ld_pc(cpu : &mut CPU) -> u8 {
 
 /// **0xE5** - *PUSH hl* - Push hl onto the stack
 pub fn push_hl(cpu : &mut CPU) -> u8 {
-    cpu.regs.sp -= 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
     cpu.mem.write_short(cpu.regs.sp, cpu.regs.get_hl());
 
     return 16 /* Cycles */;
@@ -124,7 +124,7 @@ pub fn push_hl(cpu : &mut CPU) -> u8 {
 pub fn ld_pnn_a(cpu : &mut CPU) -> u8 {
     // Read PC short
     let value = cpu.mem.read_short(cpu.regs.pc);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
     // Write it
     cpu.mem.write(value, cpu.regs.a);
 
@@ -136,7 +136,7 @@ pub fn ldh_a_pn(cpu : &mut CPU) -> u8 {
     let value = 0xFF00 + (cpu.mem.read(cpu.regs.pc) as u16 & 0xFF);
     //println!("New value: {:04x}", value);
     cpu.regs.a = cpu.mem.read(value);
-    cpu.regs.pc += 1;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(1);
 
     return 12 /* Cycles */;
 }
@@ -145,7 +145,7 @@ pub fn ldh_a_pn(cpu : &mut CPU) -> u8 {
 pub fn pop_af(cpu : &mut CPU) -> u8 {
     let value = cpu.mem.read_short(cpu.regs.sp);
     cpu.regs.set_af(value);
-    cpu.regs.sp += 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_add(2);
 
     return 12 /* Cycles */;
 }
@@ -160,7 +160,7 @@ pub fn ld_a_ptrc(cpu : &mut CPU) -> u8 {
 
 /// **0xF5** - *PUSH af* - Push af onto the stack
 pub fn push_af(cpu : &mut CPU) -> u8 {
-    cpu.regs.sp -= 2;
+    cpu.regs.sp = cpu.regs.sp.wrapping_sub(2);
     cpu.mem.write_short(cpu.regs.sp, cpu.regs.get_af());
 
     return 16 /* Cycles */;
@@ -205,7 +205,7 @@ pub fn ld_sp_hl(cpu : &mut CPU) -> u8 {
 pub fn ld_a_pnn(cpu : &mut CPU) -> u8 {
     // TODO: WTF?
     cpu.regs.a = cpu.mem.read(cpu.mem.read_short(cpu.regs.pc) & 0xFFFF);
-    cpu.regs.pc += 2;
+    cpu.regs.pc = cpu.regs.pc.wrapping_add(2);
 
     return 16 /* Cycles */;
 }
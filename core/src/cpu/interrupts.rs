@@ -28,4 +28,15 @@ impl InterruptType {
             }
         }
     }
+
+    /// Returns a short, human-readable name for this interrupt.
+    pub fn name(&self) -> &'static str {
+        return match *self {
+            InterruptType::VBLANK => "VBlank",
+            InterruptType::LCDC => "LCD STAT",
+            InterruptType::TIMER => "Timer",
+            InterruptType::SERIAL => "Serial",
+            InterruptType::KEYPAD => "Joypad"
+        }
+    }
 }
\ No newline at end of file
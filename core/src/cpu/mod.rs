@@ -15,6 +15,11 @@ use cpu::regs::Registers;
 use cpu::instrs::execute_instruction;
 use cpu::interrupts::InterruptType;
 
+use profiler::Subsystem;
+
+use hooks;
+use hooks::HookEvent;
+
 #[derive(Serialize, Deserialize)]
 pub struct CPU {
     pub regs : Registers,
@@ -87,6 +92,10 @@ impl CPU {
         match *debugger {
             Some(ref mut boxed) => {
                 boxed.debug(self);
+
+                if boxed.is_paused() {
+                    return false;
+                }
             },
             _ => {}
         }
@@ -95,6 +104,12 @@ impl CPU {
             // Read instruction
             let current_instr = self.regs.pc;
 
+            self.mem.record_execution(current_instr);
+
+            if self.mem.hooks.watches_execute(current_instr) {
+                hooks::dispatch(&mut self.mem, HookEvent::Execute { address : current_instr });
+            }
+
             let mut raw_instruction = self.mem.read(current_instr) as u16;
 
             //println!("{:02X} = {:02X}", current_instr, raw_instruction);
@@ -102,15 +117,19 @@ impl CPU {
             self.regs.pc = self.regs.pc.wrapping_add(1);
 
             if raw_instruction == 0xCB {
-                raw_instruction = ((self.mem.read(current_instr + 1) as u16) << 8) | (raw_instruction);
+                raw_instruction = ((self.mem.read(current_instr.wrapping_add(1)) as u16) << 8) | (raw_instruction);
                 self.regs.pc = self.regs.pc.wrapping_add(1);
             }
 
+            self.mem.profile_count(Subsystem::Cpu, 1);
+
             execute_instruction(self, raw_instruction, current_instr)
         } else {
             64 // TODO: Is this really the best?
         };
 
+        hooks::dispatch_pending(&mut self.mem);
+
         // After
         // Handle interrupt toggle
         if self.interrupts_countdown > -1 {
@@ -131,16 +150,28 @@ impl CPU {
         }
 
         // Handle audio
+        let previous = self.mem.profile_enter(Subsystem::Apu);
+        let samples = self.mem.sound.pending_samples();
         self.mem.sound.step(&mut self.mem.ioregs, cycles);
+        let samples = self.mem.sound.pending_samples() - samples;
+        self.mem.profile_count(Subsystem::Apu, samples as u64);
+        self.mem.profile_exit(previous);
 
         // Handle GPU
+        let previous = self.mem.profile_enter(Subsystem::Ppu);
+        let line = self.mem.gpu.current_line;
         let gpu_result = self.mem.gpu.step(cycles as u32);
+        if self.mem.gpu.current_line != line {
+            self.mem.profile_count(Subsystem::Ppu, 1);
+        }
+        self.mem.profile_exit(previous);
 
         match gpu_result {
             Some(value) => {
                 //println!("GPU throwing interrupt: {:?}", value);
                 self.throw_interrupt(value);
                 if value == InterruptType::VBLANK {
+                    self.mem.end_frame();
                     return true
                 }
             }
@@ -150,14 +181,40 @@ impl CPU {
         return false;
     }
 
-    /// Runs a iteration of the CPU
-    pub fn run(&mut self, mut debugger : &mut Option<&mut GameboyDebugger>) {
+    /// Runs a iteration of the CPU. Returns false if the debugger paused execution
+    ///  before the frame was completed.
+    pub fn run(&mut self, mut debugger : &mut Option<&mut GameboyDebugger>) -> bool {
         self.cycle_counter = 0;
         self.timer_invoke_counter = 0;
 
-        while !self.tick(&mut debugger) {}
+        // Anything not charged to another subsystem is the CPU's
+        let previous = self.mem.profile_enter(Subsystem::Cpu);
+        self.mem.trace_begin("Frame", "frame");
+
+        loop {
+            if let Some(ref boxed) = *debugger {
+                if boxed.is_paused() {
+                    self.mem.trace_end("Frame", "frame");
+                    self.mem.profile_exit(previous);
+                    return false;
+                }
+            }
+
+            if self.tick(&mut debugger) {
+                break;
+            }
+        }
+
+        self.mem.trace_end("Frame", "frame");
+        self.mem.profile_exit(previous);
+
+        if !self.mem.hooks.is_empty() {
+            hooks::dispatch(&mut self.mem, HookEvent::Frame);
+        }
 
         //println!("Counts: {} + {}", self.cycle_counter, self.timer_invoke_counter);
+
+        return true;
     }
 
     /// Registers that a interrupt should be thrown.
@@ -194,10 +251,12 @@ impl CPU {
 
         self.interrupts_enabled = false;
 
+        self.mem.trace_instant(interrupt.name(), "interrupt", Some(("pc", self.regs.pc as u32)));
+
         //println!("Throwing interrupt: {:?}", interrupt);
 
         // Push PC to stack
-        self.regs.sp -= 2;
+        self.regs.sp = self.regs.sp.wrapping_sub(2);
         self.mem.write_short(self.regs.sp, self.regs.pc);
 
         // Jump to interrupt service
@@ -252,4 +311,10 @@ impl CPU {
 
 pub trait GameboyDebugger {
     fn debug(&mut self, cpu : &mut CPU);
+
+    /// If execution should be held before the current instruction. Debuggers which
+    ///  block within `debug` don't need to implement this.
+    fn is_paused(&self) -> bool {
+        false
+    }
 }
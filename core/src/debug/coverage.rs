@@ -0,0 +1,82 @@
+/**
+ * debug/coverage.rs
+ *
+ * Records which ROM bytes have been executed as the start of an instruction, so
+ *  code can be told apart from data when disassembling a whole ROM.
+**/
+
+use alloc::Vec;
+
+/// The size of a switchable ROM bank.
+pub const BANK_SIZE : usize = 0x4000;
+
+/// A bitmap over a ROM, one bit per byte.
+pub struct CodeCoverage {
+    bits : Vec<u8>,
+    size : usize
+}
+
+impl CodeCoverage {
+    /// Converts an address in the CPU's view into an offset into the ROM, if it
+    ///  is in ROM.
+    fn offset(address : u16, bank : u8) -> Option<usize> {
+        match address {
+            0x0000 ... 0x3FFF => Some(address as usize),
+            0x4000 ... 0x7FFF => Some(bank as usize * BANK_SIZE + (address as usize - 0x4000)),
+            _ => None
+        }
+    }
+
+    /// Marks the instruction at an address as executed, with `bank` mapped at
+    ///  0x4000 - 0x7FFF. Code run from RAM is ignored.
+    pub fn record(&mut self, address : u16, bank : u8) {
+        if let Some(offset) = CodeCoverage::offset(address, bank) {
+            if offset < self.size {
+                self.bits[offset / 8] |= 1 << (offset % 8);
+            }
+        }
+    }
+
+    /// Returns if an instruction was executed starting at a ROM offset.
+    pub fn is_code(&self, offset : usize) -> bool {
+        if offset >= self.size {
+            return false;
+        }
+
+        return (self.bits[offset / 8] >> (offset % 8)) & 0x1 == 1;
+    }
+
+    /// Returns how many instructions have been seen.
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    /// Merges in coverage from another run of the same ROM.
+    pub fn merge(&mut self, other : &CodeCoverage) {
+        for (bits, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *bits |= *other;
+        }
+    }
+
+    /// Returns the bitmap, for saving. Bit `n % 8` of byte `n / 8` is set if an
+    ///  instruction started at ROM offset `n`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// Rebuilds coverage from a saved bitmap. The bitmap is truncated or padded
+    ///  to fit the ROM.
+    pub fn from_bytes(mut bits : Vec<u8>, size : usize) -> CodeCoverage {
+        bits.resize((size + 7) / 8, 0);
+
+        CodeCoverage {
+            bits,
+            size
+        }
+    }
+
+    /// Builds empty coverage for a ROM of `size` bytes.
+    pub fn build(size : usize) -> CodeCoverage {
+        CodeCoverage::from_bytes(Vec::new(), size)
+    }
+}
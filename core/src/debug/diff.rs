@@ -0,0 +1,214 @@
+/**
+ * debug/diff.rs
+ *
+ * Compares two machine states field by field, for tracking down why two runs (or
+ *  two builds) diverged.
+**/
+
+use alloc::Vec;
+
+use core::fmt;
+
+use audit::hash_state;
+use audit::StateHash;
+use audit::COMPONENTS;
+
+use cpu::CPU;
+
+/// A single difference between two states. "Left" is the first state given.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Difference {
+    /// A register or flag differs.
+    Register { name : &'static str, left : u32, right : u32 },
+    /// A block of memory differs. The values are those at the first differing offset.
+    Memory { name : &'static str, offset : usize, count : usize, left : u8, right : u8 },
+    /// A block of memory is a different size in each state.
+    Length { name : &'static str, left : usize, right : usize },
+    /// Internal state which can't be compared field by field differs.
+    Opaque { name : &'static str }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Difference::Register { name, left, right } =>
+                write!(f, "{}: {:#X} != {:#X}", name, left, right),
+            Difference::Memory { name, offset, count, left, right } =>
+                write!(f, "{}: {} byte(s) differ, first at +{:#06X} ({:#04X} != {:#04X})",
+                       name, count, offset, left, right),
+            Difference::Length { name, left, right } =>
+                write!(f, "{}: {} bytes != {} bytes", name, left, right),
+            Difference::Opaque { name } =>
+                write!(f, "{}: internal state differs", name)
+        }
+    }
+}
+
+struct Differ<'a> {
+    left : &'a CPU,
+    right : &'a CPU,
+    left_hash : StateHash,
+    right_hash : StateHash,
+    differences : Vec<Difference>
+}
+
+impl<'a> Differ<'a> {
+    /// Compares the fields of one of the components hashed by `audit`. If its hash
+    ///  differs but none of its fields do, the difference is in state which isn't
+    ///  compared field by field.
+    fn component(&mut self, name : &'static str, compare : fn(&mut Differ<'a>)) {
+        let start = self.differences.len();
+
+        compare(self);
+
+        let index = COMPONENTS.iter().position(|component| *component == name)
+            .expect("Unknown component");
+
+        if self.differences.len() == start &&
+            self.left_hash.components[index] != self.right_hash.components[index] {
+            self.differences.push(Difference::Opaque { name });
+        }
+    }
+
+    fn register<T : Into<u32>>(&mut self, name : &'static str, read : fn(&CPU) -> T) {
+        let left = read(self.left).into();
+        let right = read(self.right).into();
+
+        if left != right {
+            self.differences.push(Difference::Register { name, left, right });
+        }
+    }
+
+    fn memory(&mut self, name : &'static str, read : fn(&CPU) -> &[u8]) {
+        let left = read(self.left);
+        let right = read(self.right);
+
+        if left.len() != right.len() {
+            self.differences.push(Difference::Length {
+                name,
+                left : left.len(),
+                right : right.len()
+            });
+            return;
+        }
+
+        let mut differing = left.iter().zip(right.iter())
+            .enumerate()
+            .filter(|&(_, (x, y))| x != y);
+
+        if let Some((offset, (&first_left, &first_right))) = differing.next() {
+            self.differences.push(Difference::Memory {
+                name,
+                offset,
+                count : 1 + differing.count(),
+                left : first_left,
+                right : first_right
+            });
+        }
+    }
+}
+
+/// Compares two states, returning every difference found. States are compared
+///  as they would be saved - debugging aids such as hooks and cheats are ignored.
+pub fn diff_states(left : &CPU, right : &CPU) -> Vec<Difference> {
+    let mut differ = Differ {
+        left,
+        right,
+        left_hash : hash_state(left),
+        right_hash : hash_state(right),
+        differences : Vec::new()
+    };
+
+    differ.component("CPU", |differ| {
+        differ.register("A", |cpu| cpu.regs.a);
+        differ.register("B", |cpu| cpu.regs.b);
+        differ.register("C", |cpu| cpu.regs.c);
+        differ.register("D", |cpu| cpu.regs.d);
+        differ.register("E", |cpu| cpu.regs.e);
+        differ.register("F", |cpu| cpu.regs.f);
+        differ.register("H", |cpu| cpu.regs.h);
+        differ.register("L", |cpu| cpu.regs.l);
+        differ.register("SP", |cpu| cpu.regs.sp);
+        differ.register("PC", |cpu| cpu.regs.pc);
+        differ.register("IME", |cpu| cpu.interrupts_enabled as u8);
+        differ.register("IME countdown", |cpu| cpu.interrupts_countdown as u8);
+        differ.register("Stopped", |cpu| cpu.stopped as u8);
+        differ.register("Halted", |cpu| cpu.halted as u8);
+        differ.register("Timer armed", |cpu| cpu.timer_armed as u8);
+        differ.register("Timer counter", |cpu| cpu.timer_counter as u32);
+        differ.register("Timer enabled", |cpu| cpu.timer_enabled as u8);
+        differ.register("Cycle counter", |cpu| cpu.cycle_counter);
+        differ.register("Timer invoke counter", |cpu| cpu.timer_invoke_counter);
+    });
+
+    differ.component("Work RAM", |differ| {
+        differ.memory("Work RAM", |cpu| &cpu.mem.ram[..]);
+        differ.memory("High RAM", |cpu| &cpu.mem.high_ram[..]);
+    });
+
+    differ.component("Video", |differ| {
+        differ.register("PPU mode", |cpu| cpu.mem.gpu.mode as u8);
+        differ.register("LCDC", |cpu| cpu.mem.gpu.lcdc);
+        differ.register("STAT", |cpu| cpu.mem.gpu.stat);
+        differ.register("LY", |cpu| cpu.mem.gpu.current_line);
+        differ.register("LYC", |cpu| cpu.mem.gpu.lyc);
+        differ.register("SCX", |cpu| cpu.mem.gpu.scx);
+        differ.register("SCY", |cpu| cpu.mem.gpu.scy);
+        differ.register("WX", |cpu| cpu.mem.gpu.wx);
+        differ.register("WY", |cpu| cpu.mem.gpu.wy);
+        differ.register("BGP", |cpu| cpu.mem.gpu.bgp);
+        differ.register("OBP0", |cpu| cpu.mem.gpu.obp0);
+        differ.register("OBP1", |cpu| cpu.mem.gpu.obp1);
+        differ.register("PPU clock", |cpu| cpu.mem.gpu.internal_clock);
+        differ.memory("VRAM", |cpu| &cpu.mem.gpu.vram[..]);
+        differ.memory("OAM", |cpu| &cpu.mem.gpu.oam[..]);
+        differ.memory("Framebuffer", |cpu| &cpu.mem.gpu.pixel_data[..]);
+    });
+
+    // The APU's channels are private to it, so it is only ever compared by hash
+    differ.component("Audio", |_| {});
+
+    differ.component("Cartridge", |differ| {
+        differ.register("ROM bank", |cpu| cpu.mem.rom.current_bank());
+        differ.memory("Cart RAM", |cpu| &cpu.mem.rom.cart_ram[..]);
+    });
+
+    differ.component("I/O", |differ| {
+        differ.register("IE", |cpu| cpu.mem.interrupt_reg);
+        differ.register("IF", |cpu| cpu.mem.ioregs.iflag);
+        differ.register("P1", |cpu| cpu.mem.ioregs.p1);
+        differ.register("SB", |cpu| cpu.mem.ioregs.sb);
+        differ.register("SC", |cpu| cpu.mem.ioregs.sc);
+        differ.register("DIV", |cpu| cpu.mem.ioregs.div);
+        differ.register("TIMA", |cpu| cpu.mem.ioregs.tima);
+        differ.register("TMA", |cpu| cpu.mem.ioregs.tma);
+        differ.register("TAC", |cpu| cpu.mem.ioregs.tac);
+        differ.register("NR10", |cpu| cpu.mem.ioregs.nr10);
+        differ.register("NR11", |cpu| cpu.mem.ioregs.nr11);
+        differ.register("NR12", |cpu| cpu.mem.ioregs.nr12);
+        differ.register("NR13", |cpu| cpu.mem.ioregs.nr13);
+        differ.register("NR14", |cpu| cpu.mem.ioregs.nr14);
+        differ.register("NR21", |cpu| cpu.mem.ioregs.nr21);
+        differ.register("NR22", |cpu| cpu.mem.ioregs.nr22);
+        differ.register("NR23", |cpu| cpu.mem.ioregs.nr23);
+        differ.register("NR24", |cpu| cpu.mem.ioregs.nr24);
+        differ.register("NR30", |cpu| cpu.mem.ioregs.nr30);
+        differ.register("NR31", |cpu| cpu.mem.ioregs.nr31);
+        differ.register("NR32", |cpu| cpu.mem.ioregs.nr32);
+        differ.register("NR33", |cpu| cpu.mem.ioregs.nr33);
+        differ.register("NR34", |cpu| cpu.mem.ioregs.nr34);
+        differ.register("NR41", |cpu| cpu.mem.ioregs.nr41);
+        differ.register("NR42", |cpu| cpu.mem.ioregs.nr42);
+        differ.register("NR43", |cpu| cpu.mem.ioregs.nr43);
+        differ.register("NR44", |cpu| cpu.mem.ioregs.nr44);
+        differ.register("NR50", |cpu| cpu.mem.ioregs.nr50);
+        differ.register("NR51", |cpu| cpu.mem.ioregs.nr51);
+        differ.register("NR52", |cpu| cpu.mem.ioregs.nr52);
+        differ.register("DMA", |cpu| cpu.mem.ioregs.dma);
+        differ.register("Joypad P14", |cpu| cpu.mem.buttons.p14);
+        differ.register("Joypad P15", |cpu| cpu.mem.buttons.p15);
+        differ.memory("Wave RAM", |cpu| &cpu.mem.ioregs.wave[..]);
+    });
+
+    differ.differences
+}
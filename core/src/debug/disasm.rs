@@ -0,0 +1,284 @@
+/**
+ * disasm.rs
+ *
+ * Disassembles Gameboy instructions into RGBDS-style assembly.
+**/
+
+use alloc::String;
+use alloc::Vec;
+
+/// 8-bit register operands, in opcode order.
+const REGISTERS : [&'static str; 8] = ["b", "c", "d", "e", "h", "l", "[hl]", "a"];
+
+/// 16-bit register pair operands, for loads/arithmetic.
+const REGISTER_PAIRS : [&'static str; 4] = ["bc", "de", "hl", "sp"];
+
+/// 16-bit register pair operands, for push/pop.
+const STACK_PAIRS : [&'static str; 4] = ["bc", "de", "hl", "af"];
+
+const CONDITIONS : [&'static str; 4] = ["nz", "z", "nc", "c"];
+
+const ALU_OPS : [&'static str; 8] = ["add", "adc", "sub", "sbc", "and", "xor", "or", "cp"];
+
+const ROTATE_OPS : [&'static str; 8] = ["rlc", "rrc", "rl", "rr", "sla", "sra", "swap", "srl"];
+
+/// A single decoded instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub address : u16,
+    /// The length of the instruction, in bytes (1 - 3).
+    pub length : u8,
+    pub bytes : [u8; 3],
+    pub text : String,
+    /// Where a jump, call or restart may transfer control to, if known statically.
+    pub target : Option<u16>,
+    /// If control never falls through to the next instruction (unconditional
+    ///  jumps and returns).
+    pub terminates : bool
+}
+
+impl Instruction {
+    /// Returns the address of the instruction which follows this one.
+    pub fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.length as u16)
+    }
+
+    /// Returns if this instruction calls a subroutine (and so returns to the
+    ///  next instruction later).
+    pub fn is_call(&self) -> bool {
+        self.text.starts_with("call") || self.text.starts_with("rst")
+    }
+}
+
+/// Formats a signed relative jump into its absolute target.
+fn relative_target(address : u16, offset : u8) -> u16 {
+    address.wrapping_add(2).wrapping_add(offset as i8 as i16 as u16)
+}
+
+/// Formats a signed 8-bit offset as "+$XX"/"-$XX".
+fn signed_offset(offset : u8) -> String {
+    let offset = offset as i8;
+
+    if offset < 0 {
+        format!("-${:02X}", -(offset as i16))
+    } else {
+        format!("+${:02X}", offset)
+    }
+}
+
+/// Disassembles the instruction at the specified address.
+///
+/// * `read` - Reads a byte from the address space being disassembled.
+pub fn disassemble<F>(read : F, address : u16) -> Instruction where F : Fn(u16) -> u8 {
+    let opcode = read(address);
+    let n = read(address.wrapping_add(1));
+    let nn = (n as u16) | ((read(address.wrapping_add(2)) as u16) << 8);
+
+    let x = opcode >> 6;
+    let y = ((opcode >> 3) & 0x7) as usize;
+    let z = opcode & 0x7;
+    let p = y >> 1;
+    let q = y & 0x1;
+
+    let mut target = None;
+    let mut terminates = false;
+
+    let (length, text) : (u8, String) = match (x, z) {
+        (0, 0) => match y {
+            0 => (1, String::from("nop")),
+            1 => (3, format!("ld [${:04X}], sp", nn)),
+            2 => (2, String::from("stop")),
+            3 => {
+                let dest = relative_target(address, n);
+                target = Some(dest);
+                terminates = true;
+                (2, format!("jr ${:04X}", dest))
+            },
+            _ => {
+                let dest = relative_target(address, n);
+                target = Some(dest);
+                (2, format!("jr {}, ${:04X}", CONDITIONS[y - 4], dest))
+            }
+        },
+        (0, 1) => if q == 0 {
+            (3, format!("ld {}, ${:04X}", REGISTER_PAIRS[p], nn))
+        } else {
+            (1, format!("add hl, {}", REGISTER_PAIRS[p]))
+        },
+        (0, 2) => {
+            let pointer = ["[bc]", "[de]", "[hl+]", "[hl-]"][p];
+            if q == 0 {
+                (1, format!("ld {}, a", pointer))
+            } else {
+                (1, format!("ld a, {}", pointer))
+            }
+        },
+        (0, 3) => if q == 0 {
+            (1, format!("inc {}", REGISTER_PAIRS[p]))
+        } else {
+            (1, format!("dec {}", REGISTER_PAIRS[p]))
+        },
+        (0, 4) => (1, format!("inc {}", REGISTERS[y])),
+        (0, 5) => (1, format!("dec {}", REGISTERS[y])),
+        (0, 6) => (2, format!("ld {}, ${:02X}", REGISTERS[y], n)),
+        (0, 7) => (1, String::from(["rlca", "rrca", "rla", "rra",
+                                    "daa", "cpl", "scf", "ccf"][y])),
+        (1, _) => if y == 6 && z == 6 {
+            (1, String::from("halt"))
+        } else {
+            (1, format!("ld {}, {}", REGISTERS[y], REGISTERS[z as usize]))
+        },
+        (2, _) => (1, format!("{} a, {}", ALU_OPS[y], REGISTERS[z as usize])),
+        (3, 0) => match y {
+            0 ... 3 => (1, format!("ret {}", CONDITIONS[y])),
+            4 => (2, format!("ldh [$FF{:02X}], a", n)),
+            5 => (2, format!("add sp, {}", signed_offset(n))),
+            6 => (2, format!("ldh a, [$FF{:02X}]", n)),
+            _ => (2, format!("ld hl, sp{}", signed_offset(n)))
+        },
+        (3, 1) => if q == 0 {
+            (1, format!("pop {}", STACK_PAIRS[p]))
+        } else {
+            match p {
+                0 => {
+                    terminates = true;
+                    (1, String::from("ret"))
+                },
+                1 => {
+                    terminates = true;
+                    (1, String::from("reti"))
+                },
+                2 => {
+                    terminates = true;
+                    (1, String::from("jp hl"))
+                },
+                _ => (1, String::from("ld sp, hl"))
+            }
+        },
+        (3, 2) => match y {
+            0 ... 3 => {
+                target = Some(nn);
+                (3, format!("jp {}, ${:04X}", CONDITIONS[y], nn))
+            },
+            4 => (1, String::from("ldh [c], a")),
+            5 => (3, format!("ld [${:04X}], a", nn)),
+            6 => (1, String::from("ldh a, [c]")),
+            _ => (3, format!("ld a, [${:04X}]", nn))
+        },
+        (3, 3) => match y {
+            0 => {
+                target = Some(nn);
+                terminates = true;
+                (3, format!("jp ${:04X}", nn))
+            },
+            1 => {
+                let cb = n;
+                let register = REGISTERS[(cb & 0x7) as usize];
+                let bit = (cb >> 3) & 0x7;
+
+                (2, match cb >> 6 {
+                    0 => format!("{} {}", ROTATE_OPS[bit as usize], register),
+                    1 => format!("bit {}, {}", bit, register),
+                    2 => format!("res {}, {}", bit, register),
+                    _ => format!("set {}, {}", bit, register)
+                })
+            },
+            6 => (1, String::from("di")),
+            7 => (1, String::from("ei")),
+            _ => (1, format!("db ${:02X}", opcode))
+        },
+        (3, 4) => match y {
+            0 ... 3 => {
+                target = Some(nn);
+                (3, format!("call {}, ${:04X}", CONDITIONS[y], nn))
+            },
+            _ => (1, format!("db ${:02X}", opcode))
+        },
+        (3, 5) => if q == 0 {
+            (1, format!("push {}", STACK_PAIRS[p]))
+        } else if p == 0 {
+            target = Some(nn);
+            (3, format!("call ${:04X}", nn))
+        } else {
+            (1, format!("db ${:02X}", opcode))
+        },
+        (3, 6) => (2, format!("{} a, ${:02X}", ALU_OPS[y], n)),
+        _ => {
+            let dest = (y as u16) * 8;
+            target = Some(dest);
+            (1, format!("rst ${:02X}", dest))
+        }
+    };
+
+    let mut bytes = [0u8; 3];
+    for i in 0 .. length as usize {
+        bytes[i] = read(address.wrapping_add(i as u16));
+    }
+
+    Instruction {
+        address,
+        length,
+        bytes,
+        text,
+        target,
+        terminates
+    }
+}
+
+/// Disassembles `count` instructions, starting at the specified address.
+pub fn disassemble_range<F>(read : F, address : u16, count : usize) -> Vec<Instruction>
+    where F : Fn(u16) -> u8 {
+    let mut output = Vec::with_capacity(count);
+    let mut address = address;
+
+    for _ in 0 .. count {
+        let instruction = disassemble(&read, address);
+        address = instruction.next_address();
+        output.push(instruction);
+    }
+
+    output
+}
+
+/// Disassembles instructions either side of an address (typically PC).
+///
+/// Instructions are variable length, so there is no way to know for sure where
+///  earlier instructions begin - this picks the furthest start point which
+///  decodes cleanly into the requested address.
+pub fn disassemble_around<F>(read : F, address : u16, before : usize,
+                             after : usize) -> Vec<Instruction> where F : Fn(u16) -> u8 {
+    let max_back = (before * 3) as u16;
+    let mut start = address;
+
+    for distance in (1 .. max_back + 1).rev() {
+        let candidate = address.wrapping_sub(distance);
+
+        let mut current = candidate;
+        let mut count = 0;
+        while current != address && count <= before * 3 {
+            current = disassemble(&read, current).next_address();
+            count += 1;
+        }
+
+        if current == address {
+            start = candidate;
+            break;
+        }
+    }
+
+    let mut output = Vec::new();
+    let mut current = start;
+    while current != address {
+        let instruction = disassemble(&read, current);
+        current = instruction.next_address();
+        output.push(instruction);
+    }
+
+    // Only keep the closest instructions before the address
+    let skip = output.len().saturating_sub(before);
+    let mut output : Vec<Instruction> = output.into_iter().skip(skip).collect();
+
+    output.extend(disassemble_range(&read, address, after + 1));
+
+    output
+}
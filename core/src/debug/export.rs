@@ -0,0 +1,201 @@
+/**
+ * debug/export.rs
+ *
+ * Disassembles whole ROM banks into RGBDS-style listings, annotated with
+ *  addresses, raw bytes and labels for jump targets.
+**/
+
+use alloc::String;
+use alloc::Vec;
+
+use core::fmt::Write;
+
+use debug::coverage::CodeCoverage;
+use debug::coverage::BANK_SIZE;
+use debug::disasm::disassemble;
+use debug::disasm::Instruction;
+
+/// How many bytes are listed per `db` line.
+const DATA_PER_LINE : usize = 8;
+
+/// The column comments start at.
+const COMMENT_COLUMN : usize = 28;
+
+enum Item {
+    Code(Instruction),
+    /// A run of data, as an offset into the bank and a length.
+    Data(usize, usize)
+}
+
+/// Returns how many banks a ROM is made of.
+pub fn bank_count(rom : &[u8]) -> usize {
+    (rom.len() + BANK_SIZE - 1) / BANK_SIZE
+}
+
+/// Returns where a bank appears in the CPU's view.
+fn window(bank : usize) -> u16 {
+    if bank == 0 { 0x0000 } else { 0x4000 }
+}
+
+fn label(bank : usize, address : u16) -> String {
+    format!("L{:02X}_{:04X}", bank, address)
+}
+
+/// Splits a bank into instructions and data. Without coverage, everything which
+///  decodes is assumed to be code.
+fn split(rom : &[u8], bank : usize, coverage : Option<&CodeCoverage>) -> Vec<Item> {
+    let start = bank * BANK_SIZE;
+    let size = rom.len().saturating_sub(start).min(BANK_SIZE);
+    let window = window(bank);
+
+    let read = |address : u16| {
+        let offset = address.wrapping_sub(window) as usize;
+        if offset < size { rom[start + offset] } else { 0xFF }
+    };
+
+    let mut items = Vec::new();
+    let mut offset = 0;
+
+    while offset < size {
+        let is_code = coverage.map_or(true, |coverage| coverage.is_code(start + offset));
+
+        if is_code {
+            let instruction = disassemble(&read, window + offset as u16);
+
+            // Instructions can't run off the end of a bank
+            if offset + instruction.length as usize <= size {
+                offset += instruction.length as usize;
+                items.push(Item::Code(instruction));
+                continue;
+            }
+        }
+
+        // Extend the last run of data, if there is one
+        if let Some(&mut Item::Data(_, ref mut length)) = items.last_mut() {
+            *length += 1;
+            offset += 1;
+            continue;
+        }
+
+        items.push(Item::Data(offset, 1));
+        offset += 1;
+    }
+
+    items
+}
+
+/// Pads a line out to where its comment starts.
+fn pad(output : &mut String, line_start : usize) {
+    let length = output.len() - line_start;
+
+    for _ in length .. COMMENT_COLUMN.max(length + 1) {
+        output.push(' ');
+    }
+}
+
+/// Disassembles a single bank of a ROM into a listing which RGBDS can assemble.
+///
+/// * `coverage` - If given, only bytes which were executed are treated as code -
+///   everything else is listed as data.
+pub fn export_bank(rom : &[u8], bank : usize, coverage : Option<&CodeCoverage>) -> String {
+    let items = split(rom, bank, coverage);
+    let window = window(bank);
+
+    // Labels can go at the start of an instruction, or anywhere in data
+    let mut labelled = vec![false; BANK_SIZE];
+    for item in &items {
+        match *item {
+            Item::Code(ref instruction) => {
+                labelled[instruction.address.wrapping_sub(window) as usize] = true;
+            },
+            Item::Data(start, length) => {
+                for offset in start .. start + length {
+                    labelled[offset] = true;
+                }
+            }
+        }
+    }
+
+    // Label anything jumped to within this bank, unless it lands mid-instruction
+    let mut labels : Vec<u16> = items.iter()
+        .filter_map(|item| match *item {
+            Item::Code(ref instruction) => instruction.target,
+            _ => None
+        })
+        .filter(|target| *target >= window && *target < window + BANK_SIZE as u16)
+        .filter(|target| labelled[target.wrapping_sub(window) as usize])
+        .collect();
+    labels.sort();
+    labels.dedup();
+
+    let mut output = String::new();
+
+    if bank == 0 {
+        let _ = writeln!(output, "SECTION \"ROM Bank $00\", ROM0[$0000]");
+    } else {
+        let _ = writeln!(output, "SECTION \"ROM Bank ${:02X}\", ROMX[$4000], BANK[${:02X}]",
+                         bank, bank);
+    }
+
+    for item in &items {
+        match *item {
+            Item::Code(ref instruction) => {
+                if labels.binary_search(&instruction.address).is_ok() {
+                    let _ = writeln!(output, "\n{}:", label(bank, instruction.address));
+                }
+
+                let line_start = output.len();
+                let _ = write!(output, "    {}", instruction.text);
+                pad(&mut output, line_start);
+
+                let _ = write!(output, "; ${:04X}:", instruction.address);
+                for byte in &instruction.bytes[.. instruction.length as usize] {
+                    let _ = write!(output, " {:02X}", byte);
+                }
+
+                if let Some(target) = instruction.target {
+                    if labels.binary_search(&target).is_ok() {
+                        let _ = write!(output, " -> {}", label(bank, target));
+                    }
+                }
+
+                output.push('\n');
+            },
+            Item::Data(start, length) => {
+                let mut offset = start;
+
+                while offset < start + length {
+                    let address = window + offset as u16;
+
+                    if labels.binary_search(&address).is_ok() {
+                        let _ = writeln!(output, "\n{}:", label(bank, address));
+                    }
+
+                    // Lines stop early at labels, so they land between lines
+                    let mut end = (offset + DATA_PER_LINE).min(start + length);
+                    if let Some(next) = labels.iter()
+                        .map(|address| address.wrapping_sub(window) as usize)
+                        .find(|next| *next > offset && *next < end) {
+                        end = next;
+                    }
+
+                    let line_start = output.len();
+                    let _ = write!(output, "    db ");
+                    for (i, byte) in rom[bank * BANK_SIZE + offset .. bank * BANK_SIZE + end]
+                        .iter().enumerate() {
+                        if i > 0 {
+                            output.push_str(", ");
+                        }
+                        let _ = write!(output, "${:02X}", byte);
+                    }
+                    pad(&mut output, line_start);
+
+                    let _ = writeln!(output, "; ${:04X}", address);
+                    offset = end;
+                }
+            }
+        }
+    }
+
+    output
+}
@@ -0,0 +1,173 @@
+/**
+ * debug/mod.rs
+ *
+ * Tools for inspecting and controlling a running system, used by frontend debuggers.
+**/
+
+pub mod coverage;
+pub mod diff;
+pub mod disasm;
+pub mod export;
+pub mod scan;
+#[cfg(feature = "profiling")]
+pub mod timing;
+pub mod vram;
+
+use alloc::Vec;
+
+use cpu::CPU;
+use cpu::GameboyDebugger;
+
+use debug::disasm::disassemble;
+
+/// Parses a hex address as typed into a debugger, with or without a "$" or "0x" prefix.
+pub fn parse_address(value : &str) -> Option<u16> {
+    let value = value.trim();
+    let value = value.trim_left_matches('$');
+    let value = value.trim_left_matches("0x");
+
+    u16::from_str_radix(value, 16).ok()
+}
+
+/// Why execution was last stopped.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BreakReason {
+    /// Paused on request.
+    Paused,
+    /// A requested number of instructions was stepped.
+    Step,
+    /// A breakpoint at the specified address was hit.
+    Breakpoint(u16)
+}
+
+/// A debugger which pauses execution on breakpoints and user request, without
+///  blocking - frontends drive it from their own UI loop.
+pub struct DebugController {
+    pub breakpoints : Vec<u16>,
+    paused : bool,
+    /// Instructions left to execute before pausing again, if stepping.
+    steps : Option<u32>,
+    /// A temporary breakpoint, for stepping over calls.
+    run_to : Option<u16>,
+    /// Set when resuming, so we don't immediately re-break on the current breakpoint.
+    skip_breakpoint : bool,
+    last_break : Option<BreakReason>
+}
+
+impl DebugController {
+    /// Returns if execution is currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns why execution last stopped, if it has.
+    pub fn last_break(&self) -> Option<BreakReason> {
+        self.last_break
+    }
+
+    /// Pauses execution before the next instruction.
+    pub fn pause(&mut self) {
+        self.stop(BreakReason::Paused);
+    }
+
+    /// Continues execution until the next breakpoint.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.steps = None;
+        self.run_to = None;
+        self.skip_breakpoint = true;
+    }
+
+    /// Executes the specified number of instructions, then pauses.
+    pub fn step(&mut self, count : u32) {
+        self.resume();
+        self.steps = Some(count);
+    }
+
+    /// Executes the next instruction, running until any subroutine it calls
+    ///  returns.
+    pub fn step_over(&mut self, cpu : &CPU) {
+        let instruction = disassemble(|x| cpu.mem.debug_read(x), cpu.regs.pc);
+
+        if instruction.is_call() {
+            self.resume();
+            self.run_to = Some(instruction.next_address());
+        } else {
+            self.step(1);
+        }
+    }
+
+    /// Adds a breakpoint, or removes it if it already exists. Returns if the
+    ///  breakpoint is now set.
+    pub fn toggle_breakpoint(&mut self, address : u16) -> bool {
+        match self.breakpoints.iter().position(|&x| x == address) {
+            Some(position) => {
+                self.breakpoints.remove(position);
+                false
+            },
+            None => {
+                self.breakpoints.push(address);
+                true
+            }
+        }
+    }
+
+    /// Returns if a breakpoint is set at the specified address.
+    pub fn has_breakpoint(&self, address : u16) -> bool {
+        self.breakpoints.contains(&address)
+    }
+
+    fn stop(&mut self, reason : BreakReason) {
+        self.paused = true;
+        self.steps = None;
+        self.run_to = None;
+        self.last_break = Some(reason);
+    }
+
+    pub fn build() -> DebugController {
+        DebugController {
+            breakpoints : Vec::new(),
+            paused : false,
+            steps : None,
+            run_to : None,
+            skip_breakpoint : false,
+            last_break : None
+        }
+    }
+}
+
+impl GameboyDebugger for DebugController {
+    fn debug(&mut self, cpu : &mut CPU) {
+        if self.paused {
+            return;
+        }
+
+        let pc = cpu.regs.pc;
+
+        let skip_breakpoint = self.skip_breakpoint;
+        self.skip_breakpoint = false;
+
+        if let Some(steps) = self.steps {
+            if steps == 0 {
+                self.stop(BreakReason::Step);
+            } else {
+                self.steps = Some(steps - 1);
+            }
+
+            return;
+        }
+
+        if self.run_to == Some(pc) {
+            self.stop(BreakReason::Step);
+            return;
+        }
+
+        if !skip_breakpoint && self.breakpoints.contains(&pc) {
+            self.stop(BreakReason::Breakpoint(pc));
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
@@ -0,0 +1,121 @@
+/**
+ * scan.rs
+ *
+ * Searches RAM for values which change in a particular way, for finding the
+ *  addresses cheats should target.
+**/
+
+use alloc::Vec;
+
+use mem::GBMemory;
+
+/// How much cart RAM is visible at once, at 0xA000.
+const CART_RAM_WINDOW : usize = 0x2000;
+
+/// How a value must compare to the previous scan to stay a candidate.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Comparison {
+    /// Equal to a specific value.
+    Exact(u8),
+    Increased,
+    Decreased,
+    Changed,
+    Unchanged
+}
+
+impl Comparison {
+    fn matches(&self, previous : u8, current : u8) -> bool {
+        match *self {
+            Comparison::Exact(value) => current == value,
+            Comparison::Increased => current > previous,
+            Comparison::Decreased => current < previous,
+            Comparison::Changed => current != previous,
+            Comparison::Unchanged => current == previous
+        }
+    }
+}
+
+/// An address which has matched every comparison so far.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Candidate {
+    pub address : u16,
+    /// The value when this address was last compared.
+    pub previous : u8
+}
+
+/// Narrows down RAM addresses over a series of scans. Covers work RAM, high
+///  RAM and any cartridge RAM.
+pub struct MemoryScanner {
+    candidates : Vec<Candidate>,
+    scans : u32
+}
+
+/// Returns every address which can be scanned on this system.
+fn scannable_addresses(mem : &GBMemory) -> Vec<u16> {
+    let cart_ram = mem.rom.cart_ram.len().min(CART_RAM_WINDOW) as u16;
+
+    let mut addresses = Vec::new();
+    addresses.extend(0xA000 .. 0xA000 + cart_ram);
+    addresses.extend(0xC000 .. 0xE000);
+    addresses.extend(0xFF80 .. 0xFFFF);
+    addresses
+}
+
+impl MemoryScanner {
+    /// Snapshots all scannable memory, making every address a candidate.
+    pub fn start(&mut self, mem : &GBMemory) {
+        self.candidates = scannable_addresses(mem).into_iter()
+            .map(|address| Candidate {
+                address,
+                previous : mem.debug_read(address)
+            })
+            .collect();
+        self.scans = 0;
+    }
+
+    /// Drops candidates which don't match a comparison against their previous
+    ///  value, then snapshots those which remain. Starts a new search if none
+    ///  is in progress.
+    pub fn filter(&mut self, mem : &GBMemory, comparison : Comparison) {
+        if !self.is_active() {
+            self.start(mem);
+        }
+
+        self.candidates.retain(|candidate| {
+            comparison.matches(candidate.previous, mem.debug_read(candidate.address))
+        });
+
+        for candidate in &mut self.candidates {
+            candidate.previous = mem.debug_read(candidate.address);
+        }
+
+        self.scans += 1;
+    }
+
+    /// Forgets the current search.
+    pub fn reset(&mut self) {
+        self.candidates.clear();
+        self.scans = 0;
+    }
+
+    /// Returns if a search has been started.
+    pub fn is_active(&self) -> bool {
+        !self.candidates.is_empty() || self.scans > 0
+    }
+
+    /// Returns how many comparisons have narrowed down this search.
+    pub fn scans(&self) -> u32 {
+        self.scans
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    pub fn build() -> MemoryScanner {
+        MemoryScanner {
+            candidates : Vec::new(),
+            scans : 0
+        }
+    }
+}
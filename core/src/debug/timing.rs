@@ -0,0 +1,58 @@
+/**
+ * debug/timing.rs
+ *
+ * Reports where emulation time went, per subsystem, for benchmarks and profiler
+ *  views. Requires the "profiling" feature.
+**/
+
+use alloc::Vec;
+
+use cpu::CPU;
+
+use profiler::Profiler;
+use profiler::Subsystem;
+use profiler::SUBSYSTEMS;
+
+/// Time and work charged to a subsystem since profiling started (or was reset).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SubsystemTiming {
+    pub subsystem : Subsystem,
+    /// Time spent, in the clock's units.
+    pub time : u64,
+    /// Work done, in the subsystem's `unit`.
+    pub count : u64
+}
+
+/// Starts profiling, if it isn't already running.
+///
+/// * `clock` - Returns the current time, such as in nanoseconds.
+pub fn start_profiling(cpu : &mut CPU, clock : fn() -> u64) {
+    if cpu.mem.profiler.is_none() {
+        cpu.mem.profiler = Some(Profiler::build(clock));
+    }
+}
+
+pub fn stop_profiling(cpu : &mut CPU) {
+    cpu.mem.profiler = None;
+}
+
+/// Clears everything measured so far.
+pub fn reset_profiling(cpu : &CPU) {
+    if let Some(ref profiler) = cpu.mem.profiler {
+        profiler.reset();
+    }
+}
+
+/// Returns what each subsystem has used, or nothing if not profiling.
+pub fn subsystem_timings(cpu : &CPU) -> Vec<SubsystemTiming> {
+    match cpu.mem.profiler {
+        Some(ref profiler) => SUBSYSTEMS.iter()
+            .map(|subsystem| SubsystemTiming {
+                subsystem : *subsystem,
+                time : profiler.total(*subsystem),
+                count : profiler.counted(*subsystem)
+            })
+            .collect(),
+        None => Vec::new()
+    }
+}
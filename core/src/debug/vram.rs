@@ -0,0 +1,215 @@
+/**
+ * vram.rs
+ *
+ * Renders the contents of VRAM and OAM into images, for debug viewers.
+**/
+
+use alloc::Vec;
+
+use gpu::GPU;
+use gpu::PITCH;
+
+/// Tiles are laid out 16 to a row in the tile sheet, for all 384 tiles.
+pub const TILE_SHEET_WIDTH : usize = 16 * 8;
+pub const TILE_SHEET_HEIGHT : usize = 24 * 8;
+
+/// Each tilemap is 32x32 tiles.
+pub const TILEMAP_SIZE : usize = 32 * 8;
+
+/// Sprites are laid out 8 to a row, each in a 8x16 cell.
+pub const SPRITE_SHEET_WIDTH : usize = 8 * 8;
+pub const SPRITE_SHEET_HEIGHT : usize = 5 * 16;
+
+/// One of the two background tilemaps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileMap {
+    /// At 0x9800.
+    Low,
+    /// At 0x9C00.
+    High
+}
+
+impl TileMap {
+    fn offset(&self) -> usize {
+        match *self {
+            TileMap::Low => 0x1800,
+            TileMap::High => 0x1C00
+        }
+    }
+
+    /// Returns the tilemap currently used for the background.
+    pub fn background(gpu : &GPU) -> TileMap {
+        if gpu.lcdc >> 3 & 0x1 == 1 { TileMap::High } else { TileMap::Low }
+    }
+
+    /// Returns the tilemap currently used for the window.
+    pub fn window(gpu : &GPU) -> TileMap {
+        if gpu.lcdc >> 6 & 0x1 == 1 { TileMap::High } else { TileMap::Low }
+    }
+}
+
+/// A decoded OAM entry.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprite {
+    pub index : usize,
+    /// Screen position, with the hardware's offsets removed.
+    pub x : i16,
+    pub y : i16,
+    pub tile : u8,
+    pub attributes : u8,
+    /// If the sprite is drawn behind non-zero background colours.
+    pub behind_background : bool,
+    pub y_flip : bool,
+    pub x_flip : bool,
+    /// Which of OBP0/OBP1 the sprite uses.
+    pub palette : u8
+}
+
+impl Sprite {
+    /// Returns if any of the sprite is within the visible screen.
+    pub fn is_visible(&self, height : i16) -> bool {
+        self.x > -8 && self.x < 160 && self.y > -height && self.y < 144
+    }
+}
+
+/// Returns the colour index (0 - 3) of a pixel within a tile.
+///
+/// * `tile_offset` - The offset of the tile's data within VRAM.
+fn tile_pixel(gpu : &GPU, tile_offset : usize, x : usize, y : usize) -> u8 {
+    let first_byte = gpu.vram[tile_offset + y * 2];
+    let second_byte = gpu.vram[tile_offset + y * 2 + 1];
+
+    let first_bit = (first_byte >> (7 - x)) & 0x1;
+    let second_bit = (second_byte >> (7 - x)) & 0x1;
+
+    first_bit | (second_bit << 1)
+}
+
+/// Maps a colour index through a palette register (BGP/OBP0/OBP1) into RGB.
+fn shade(gpu : &GPU, register : u8, index : u8) -> [u8; 3] {
+    let shade = ((register >> (index * 2)) & 0b11) as usize;
+
+    [gpu.palette[shade * PITCH], gpu.palette[shade * PITCH + 1], gpu.palette[shade * PITCH + 2]]
+}
+
+fn put_pixel(output : &mut [u8], width : usize, x : usize, y : usize, color : [u8; 3]) {
+    let pos = (y * width + x) * PITCH;
+    output[pos .. pos + PITCH].copy_from_slice(&color);
+}
+
+/// Returns the RGB colours of each entry within a palette register.
+pub fn palette_colors(gpu : &GPU, register : u8) -> [[u8; 3]; 4] {
+    [shade(gpu, register, 0), shade(gpu, register, 1),
+     shade(gpu, register, 2), shade(gpu, register, 3)]
+}
+
+/// Returns the offset within VRAM of a background/window tile, using the
+///  currently selected addressing mode.
+fn background_tile_offset(gpu : &GPU, tile : u8) -> usize {
+    if gpu.lcdc >> 4 & 0x1 == 1 {
+        tile as usize * 16
+    } else {
+        (0x1000 + (tile as i8 as isize) * 16) as usize
+    }
+}
+
+/// Renders all 384 tiles in VRAM as a RGB image, using BGP.
+pub fn render_tiles(gpu : &GPU) -> Vec<u8> {
+    let mut output = vec![0u8; TILE_SHEET_WIDTH * TILE_SHEET_HEIGHT * PITCH];
+
+    for tile in 0 .. 384 {
+        let base_x = (tile % 16) * 8;
+        let base_y = (tile / 16) * 8;
+
+        for y in 0 .. 8 {
+            for x in 0 .. 8 {
+                let index = tile_pixel(gpu, tile * 16, x, y);
+                put_pixel(&mut output, TILE_SHEET_WIDTH, base_x + x, base_y + y,
+                          shade(gpu, gpu.bgp, index));
+            }
+        }
+    }
+
+    output
+}
+
+/// Renders an entire 256x256 tilemap as a RGB image, using the current tile
+///  data addressing mode and BGP.
+pub fn render_tilemap(gpu : &GPU, map : TileMap) -> Vec<u8> {
+    let mut output = vec![0u8; TILEMAP_SIZE * TILEMAP_SIZE * PITCH];
+
+    for tile_y in 0 .. 32 {
+        for tile_x in 0 .. 32 {
+            let tile = gpu.vram[map.offset() + tile_y * 32 + tile_x];
+            let offset = background_tile_offset(gpu, tile);
+
+            for y in 0 .. 8 {
+                for x in 0 .. 8 {
+                    let index = tile_pixel(gpu, offset, x, y);
+                    put_pixel(&mut output, TILEMAP_SIZE, tile_x * 8 + x, tile_y * 8 + y,
+                              shade(gpu, gpu.bgp, index));
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Decodes all 40 entries in OAM.
+pub fn sprites(gpu : &GPU) -> Vec<Sprite> {
+    (0 .. 40).map(|index| {
+        let entry = &gpu.oam[index * 4 .. index * 4 + 4];
+        let attributes = entry[3];
+
+        Sprite {
+            index,
+            x : entry[1] as i16 - 8,
+            y : entry[0] as i16 - 16,
+            tile : entry[2],
+            attributes,
+            behind_background : attributes >> 7 & 0x1 == 1,
+            y_flip : attributes >> 6 & 0x1 == 1,
+            x_flip : attributes >> 5 & 0x1 == 1,
+            palette : attributes >> 4 & 0x1
+        }
+    }).collect()
+}
+
+/// Returns the height of sprites, as set in LCDC (8 or 16).
+pub fn sprite_height(gpu : &GPU) -> usize {
+    if gpu.lcdc >> 2 & 0x1 == 1 { 16 } else { 8 }
+}
+
+/// Renders every sprite in OAM as a RGB image, in 8x16 cells, using their palettes
+///  and flips. Colour 0 (transparent) is left black.
+pub fn render_sprites(gpu : &GPU) -> Vec<u8> {
+    let mut output = vec![0u8; SPRITE_SHEET_WIDTH * SPRITE_SHEET_HEIGHT * PITCH];
+    let height = sprite_height(gpu);
+
+    for sprite in sprites(gpu) {
+        let base_x = (sprite.index % 8) * 8;
+        let base_y = (sprite.index / 8) * 16;
+
+        // 8x16 sprites ignore the lowest bit of the tile number
+        let tile = if height == 16 { sprite.tile & 0xFE } else { sprite.tile };
+        let register = if sprite.palette == 1 { gpu.obp1 } else { gpu.obp0 };
+
+        for y in 0 .. height {
+            for x in 0 .. 8 {
+                let source_x = if sprite.x_flip { 7 - x } else { x };
+                let source_y = if sprite.y_flip { height - 1 - y } else { y };
+
+                let index = tile_pixel(gpu, tile as usize * 16, source_x, source_y);
+                if index == 0 {
+                    continue;
+                }
+
+                put_pixel(&mut output, SPRITE_SHEET_WIDTH, base_x + x, base_y + y,
+                          shade(gpu, register, index));
+            }
+        }
+    }
+
+    output
+}
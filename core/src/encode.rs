@@ -0,0 +1,315 @@
+/**
+ * encode.rs
+ *
+ * Streams serializable state as bytes in bincode's default encoding - fixed-width
+ *  little-endian integers, u64 lengths and u32 enum variants - without buffering
+ *  it, so it can be measured or hashed cheaply.
+**/
+
+use core::fmt;
+
+use serde::ser;
+use serde::Serialize;
+
+/// Receives encoded bytes.
+pub trait Sink {
+    fn write(&mut self, bytes : &[u8]);
+}
+
+/// Counts encoded bytes.
+pub struct ByteCounter(pub usize);
+
+impl Sink for ByteCounter {
+    fn write(&mut self, bytes : &[u8]) {
+        self.0 += bytes.len();
+    }
+}
+
+const FNV_OFFSET : u64 = 0xcbf29ce484222325;
+const FNV_PRIME : u64 = 0x100000001b3;
+
+/// Hashes encoded bytes with 64-bit FNV-1a.
+pub struct Fnv1a(pub u64);
+
+impl Default for Fnv1a {
+    fn default() -> Fnv1a {
+        Fnv1a(FNV_OFFSET)
+    }
+}
+
+impl Sink for Fnv1a {
+    fn write(&mut self, bytes : &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Encodes a value into a sink.
+pub fn encode<T : ?Sized + Serialize, S : Sink>(value : &T, sink : &mut S) {
+    encode_with(value, None, sink);
+}
+
+/// Encodes a struct into a sink, leaving out one of its fields (but not fields
+///  of nested structs with the same name).
+pub fn encode_without<T : ?Sized + Serialize, S : Sink>(value : &T, field : &'static str,
+                                                        sink : &mut S) {
+    encode_with(value, Some(field), sink);
+}
+
+fn encode_with<T : ?Sized + Serialize, S : Sink>(value : &T, skip_field : Option<&'static str>,
+                                                 sink : &mut S) {
+    let mut encoder = Encoder {
+        sink,
+        skip_field,
+        depth : 0
+    };
+
+    // Encoding only fails if a value's Serialize impl reports an error, which
+    //  none of ours do
+    if value.serialize(&mut encoder).is_err() {
+        warn!("Failed to encode value");
+    }
+}
+
+/// Encoding never fails, but serde requires a error type.
+#[derive(Debug)]
+pub struct EncodeError;
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("couldn't encode value")
+    }
+}
+
+impl ser::Error for EncodeError {
+    fn custom<T : fmt::Display>(_ : T) -> EncodeError {
+        EncodeError
+    }
+}
+
+struct Encoder<'s, S : Sink + 's> {
+    sink : &'s mut S,
+    /// A field of the outermost struct to leave out.
+    skip_field : Option<&'static str>,
+    /// How many structs are being encoded, outermost first.
+    depth : usize
+}
+
+impl<'s, S : Sink> Encoder<'s, S> {
+    fn write(&mut self, bytes : &[u8]) -> Result<(), EncodeError> {
+        self.sink.write(bytes);
+        Ok(())
+    }
+
+    fn write_u16(&mut self, value : u16) -> Result<(), EncodeError> {
+        self.write(&[value as u8, (value >> 8) as u8])
+    }
+
+    fn write_u32(&mut self, value : u32) -> Result<(), EncodeError> {
+        self.write_u16(value as u16)?;
+        self.write_u16((value >> 16) as u16)
+    }
+
+    fn write_u64(&mut self, value : u64) -> Result<(), EncodeError> {
+        self.write_u32(value as u32)?;
+        self.write_u32((value >> 32) as u32)
+    }
+
+    fn write_length(&mut self, length : Option<usize>) -> Result<(), EncodeError> {
+        match length {
+            Some(length) => self.write_u64(length as u64),
+            None => Err(EncodeError)
+        }
+    }
+}
+
+impl<'a, 's, S : Sink> ser::Serializer for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, value : bool) -> Result<(), EncodeError> { self.write(&[value as u8]) }
+    fn serialize_i8(self, value : i8) -> Result<(), EncodeError> { self.write(&[value as u8]) }
+    fn serialize_i16(self, value : i16) -> Result<(), EncodeError> { self.write_u16(value as u16) }
+    fn serialize_i32(self, value : i32) -> Result<(), EncodeError> { self.write_u32(value as u32) }
+    fn serialize_i64(self, value : i64) -> Result<(), EncodeError> { self.write_u64(value as u64) }
+    fn serialize_u8(self, value : u8) -> Result<(), EncodeError> { self.write(&[value]) }
+    fn serialize_u16(self, value : u16) -> Result<(), EncodeError> { self.write_u16(value) }
+    fn serialize_u32(self, value : u32) -> Result<(), EncodeError> { self.write_u32(value) }
+    fn serialize_u64(self, value : u64) -> Result<(), EncodeError> { self.write_u64(value) }
+    fn serialize_f32(self, value : f32) -> Result<(), EncodeError> { self.write_u32(value.to_bits()) }
+    fn serialize_f64(self, value : f64) -> Result<(), EncodeError> { self.write_u64(value.to_bits()) }
+
+    fn serialize_char(self, value : char) -> Result<(), EncodeError> {
+        let mut buffer = [0; 4];
+        self.write(value.encode_utf8(&mut buffer).as_bytes())
+    }
+
+    fn serialize_str(self, value : &str) -> Result<(), EncodeError> {
+        self.serialize_bytes(value.as_bytes())
+    }
+
+    fn serialize_bytes(self, value : &[u8]) -> Result<(), EncodeError> {
+        self.write_u64(value.len() as u64)?;
+        self.write(value)
+    }
+
+    fn serialize_none(self) -> Result<(), EncodeError> { self.write(&[0]) }
+
+    fn serialize_some<T : ?Sized + Serialize>(self, value : &T) -> Result<(), EncodeError> {
+        self.write(&[1])?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), EncodeError> { Ok(()) }
+    fn serialize_unit_struct(self, _ : &'static str) -> Result<(), EncodeError> { Ok(()) }
+
+    fn serialize_unit_variant(self, _ : &'static str, variant : u32,
+                              _ : &'static str) -> Result<(), EncodeError> {
+        self.write_u32(variant)
+    }
+
+    fn serialize_newtype_struct<T : ?Sized + Serialize>(self, _ : &'static str,
+                                                        value : &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T : ?Sized + Serialize>(self, _ : &'static str, variant : u32,
+                                                         _ : &'static str,
+                                                         value : &T) -> Result<(), EncodeError> {
+        self.write_u32(variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, length : Option<usize>) -> Result<Self, EncodeError> {
+        self.write_length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _ : usize) -> Result<Self, EncodeError> { Ok(self) }
+
+    fn serialize_tuple_struct(self, _ : &'static str, _ : usize) -> Result<Self, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _ : &'static str, variant : u32, _ : &'static str,
+                               _ : usize) -> Result<Self, EncodeError> {
+        self.write_u32(variant)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, length : Option<usize>) -> Result<Self, EncodeError> {
+        self.write_length(length)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _ : &'static str, _ : usize) -> Result<Self, EncodeError> {
+        self.depth += 1;
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _ : &'static str, variant : u32, _ : &'static str,
+                                _ : usize) -> Result<Self, EncodeError> {
+        self.write_u32(variant)?;
+        Ok(self)
+    }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeSeq for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T : ?Sized + Serialize>(&mut self, value : &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> { Ok(()) }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeTuple for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T : ?Sized + Serialize>(&mut self, value : &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> { Ok(()) }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeTupleStruct for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T : ?Sized + Serialize>(&mut self, value : &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> { Ok(()) }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeTupleVariant for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T : ?Sized + Serialize>(&mut self, value : &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> { Ok(()) }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeMap for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T : ?Sized + Serialize>(&mut self, key : &T) -> Result<(), EncodeError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T : ?Sized + Serialize>(&mut self, value : &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> { Ok(()) }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeStruct for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T : ?Sized + Serialize>(&mut self, key : &'static str,
+                                               value : &T) -> Result<(), EncodeError> {
+        if self.depth == 1 && self.skip_field == Some(key) {
+            return Ok(());
+        }
+
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        self.depth -= 1;
+        Ok(())
+    }
+}
+
+impl<'a, 's, S : Sink> ser::SerializeStructVariant for &'a mut Encoder<'s, S> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T : ?Sized + Serialize>(&mut self, _ : &'static str,
+                                               value : &T) -> Result<(), EncodeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), EncodeError> { Ok(()) }
+}
@@ -0,0 +1,105 @@
+/**
+ * events.rs
+ *
+ * Things which happen inside the core that a frontend may want to tell the user
+ *  about. Events are queued up until the frontend takes them, usually once per
+ *  frame.
+**/
+
+use alloc::String;
+use alloc::Vec;
+
+use core::cell::Cell;
+use core::cell::RefCell;
+use core::fmt;
+
+/// Events kept before new ones are dropped, if the frontend doesn't take them.
+const MAX_EVENTS : usize = 64;
+
+/// Distinct one-off events remembered, so they are only reported once.
+const MAX_REPORTED : usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoreEvent {
+    /// Cartridge RAM was written to during the last frame.
+    SaveRamWritten,
+    /// The game used something which isn't emulated.
+    UnsupportedFeature(String),
+    /// The game started talking over the link port. Nothing is ever connected,
+    ///  so it receives 0xFF in exchange.
+    LinkConnected,
+    /// The game sent a print command to a Game Boy Printer.
+    PrinterJobReady,
+    /// A cheat changed memory for the first time, by its code.
+    CheatFired(String),
+    /// The game did something which may not behave as it would on hardware.
+    AccuracyWarning(String)
+}
+
+impl fmt::Display for CoreEvent {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CoreEvent::SaveRamWritten => write!(f, "Save RAM written"),
+            CoreEvent::UnsupportedFeature(ref feature) => write!(f, "Unsupported: {}", feature),
+            CoreEvent::LinkConnected => write!(f, "Link cable in use"),
+            CoreEvent::PrinterJobReady => write!(f, "Print job sent"),
+            CoreEvent::CheatFired(ref code) => write!(f, "Cheat {} active", code),
+            CoreEvent::AccuracyWarning(ref warning) => write!(f, "Inaccurate: {}", warning)
+        }
+    }
+}
+
+/// Collects events until the frontend takes them.
+#[derive(Default)]
+pub struct EventQueue {
+    events : RefCell<Vec<CoreEvent>>,
+    reported : RefCell<Vec<CoreEvent>>,
+    dropped : Cell<u32>
+}
+
+impl EventQueue {
+    /// Queues an event.
+    pub fn push(&self, event : CoreEvent) {
+        match event {
+            CoreEvent::UnsupportedFeature(_) |
+            CoreEvent::AccuracyWarning(_) => warn!("{}", event),
+            _ => debug!("{}", event)
+        }
+
+        let mut events = self.events.borrow_mut();
+
+        if events.len() >= MAX_EVENTS {
+            self.dropped.set(self.dropped.get().wrapping_add(1));
+            return;
+        }
+
+        events.push(event);
+    }
+
+    /// Queues an event, unless an identical one was reported before.
+    pub fn report_once(&self, event : CoreEvent) {
+        {
+            let mut reported = self.reported.borrow_mut();
+
+            if reported.contains(&event) {
+                return;
+            }
+
+            if reported.len() < MAX_REPORTED {
+                reported.push(event.clone());
+            }
+        }
+
+        self.push(event);
+    }
+
+    /// Takes all events queued so far.
+    pub fn take_events(&self) -> Vec<CoreEvent> {
+        self.events.borrow_mut().drain(..).collect()
+    }
+
+    /// Returns how many events were dropped because they weren't taken in time.
+    pub fn dropped(&self) -> u32 {
+        self.dropped.get()
+    }
+}
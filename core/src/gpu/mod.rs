@@ -40,7 +40,11 @@ pub struct GPU {
     pub obp1 : u8,
 
     pub internal_clock : u32,
-    pub current_line : u8
+    pub current_line : u8,
+
+    /// If only 10 sprites should be drawn per line, as on hardware. Disabling this
+    ///  removes flicker in some games at the cost of accuracy.
+    pub sprite_limit : bool
 }
 
 impl GPU {
@@ -324,7 +328,7 @@ impl GPU {
                 }
 
                 sprite_row_count += 1;
-                if sprite_row_count > 10 {
+                if self.sprite_limit && sprite_row_count > 10 {
                     break
                 }
 
@@ -402,6 +406,8 @@ impl GPU {
 
             internal_clock: 0,
             current_line: 0x94,
+
+            sprite_limit: true,
         };
     }
 }
@@ -0,0 +1,208 @@
+/**
+ * hooks.rs
+ *
+ * Callbacks run when a frame completes, when watched addresses are accessed or
+ *  when watched instructions are about to execute - for auto-splitters, bots and
+ *  game-specific overlays. Callbacks get full access to memory, and can press
+ *  buttons with `GBMemory::set_input`.
+**/
+
+use alloc::boxed::Box;
+use alloc::Vec;
+
+use core::cell::RefCell;
+use core::mem::replace;
+use core::mem::size_of;
+
+use mem::GBMemory;
+
+/// What caused a hook to run.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HookEvent {
+    /// A frame was completed.
+    Frame,
+    /// A watched address was read, including by instruction fetches.
+    Read { address : u16, value : u8 },
+    /// A watched address was written to.
+    Write { address : u16, value : u8 },
+    /// A watched instruction is about to execute.
+    Execute { address : u16 }
+}
+
+pub type HookFn = Box<FnMut(&mut GBMemory, HookEvent)>;
+
+/// Identifies a registered hook, so it can be removed later.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HookId(u32);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Trigger {
+    Frame,
+    Read(u16),
+    Write(u16),
+    Execute(u16)
+}
+
+impl Trigger {
+    fn matches(&self, event : &HookEvent) -> bool {
+        match (*self, *event) {
+            (Trigger::Frame, HookEvent::Frame) => true,
+            (Trigger::Read(watched), HookEvent::Read { address, .. }) => watched == address,
+            (Trigger::Write(watched), HookEvent::Write { address, .. }) => watched == address,
+            (Trigger::Execute(watched), HookEvent::Execute { address }) => watched == address,
+            _ => false
+        }
+    }
+}
+
+struct Hook {
+    id : HookId,
+    trigger : Trigger,
+    callback : HookFn
+}
+
+/// The hooks registered on a system. Memory accesses happen in the middle of
+///  instructions, so they are queued up and their hooks run once the
+///  instruction is complete.
+///
+/// Memory accesses made by hooks themselves don't trigger other hooks, and
+///  hooks can't be removed from within a callback.
+#[derive(Default)]
+pub struct Hooks {
+    hooks : Vec<Hook>,
+    next_id : u32,
+    pending : RefCell<Vec<HookEvent>>,
+
+    // Avoids searching for hooks on every access when there are none
+    watching_reads : bool,
+    watching_writes : bool
+}
+
+impl Hooks {
+    /// Runs a callback whenever a frame is completed.
+    pub fn on_frame(&mut self, callback : HookFn) -> HookId {
+        self.add(Trigger::Frame, callback)
+    }
+
+    /// Runs a callback after each instruction which reads an address.
+    pub fn on_read(&mut self, address : u16, callback : HookFn) -> HookId {
+        self.add(Trigger::Read(address), callback)
+    }
+
+    /// Runs a callback after each instruction which writes to an address.
+    pub fn on_write(&mut self, address : u16, callback : HookFn) -> HookId {
+        self.add(Trigger::Write(address), callback)
+    }
+
+    /// Runs a callback just before the instruction at an address executes.
+    pub fn on_execute(&mut self, address : u16, callback : HookFn) -> HookId {
+        self.add(Trigger::Execute(address), callback)
+    }
+
+    /// Removes a hook. Returns false if it wasn't registered.
+    pub fn remove(&mut self, id : HookId) -> bool {
+        let count = self.hooks.len();
+        self.hooks.retain(|hook| hook.id != id);
+        self.update_watches();
+
+        self.hooks.len() != count
+    }
+
+    pub fn clear(&mut self) {
+        self.hooks.clear();
+        self.pending.borrow_mut().clear();
+        self.update_watches();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Returns how much heap memory the registry uses, in bytes. This doesn't
+    ///  include anything captured by callbacks.
+    pub fn heap_size(&self) -> usize {
+        self.hooks.capacity() * size_of::<Hook>()
+            + self.pending.borrow().capacity() * size_of::<HookEvent>()
+    }
+
+    fn add(&mut self, trigger : Trigger, callback : HookFn) -> HookId {
+        let id = HookId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+
+        self.hooks.push(Hook {
+            id,
+            trigger,
+            callback
+        });
+        self.update_watches();
+
+        id
+    }
+
+    fn update_watches(&mut self) {
+        self.watching_reads = self.hooks.iter().any(|hook| match hook.trigger {
+            Trigger::Read(_) => true,
+            _ => false
+        });
+
+        self.watching_writes = self.hooks.iter().any(|hook| match hook.trigger {
+            Trigger::Write(_) => true,
+            _ => false
+        });
+    }
+
+    fn record(&self, event : HookEvent) {
+        if self.hooks.iter().any(|hook| hook.trigger.matches(&event)) {
+            self.pending.borrow_mut().push(event);
+        }
+    }
+
+    /// Notes a read, if any hook is watching its address.
+    pub fn record_read(&self, address : u16, value : u8) {
+        if self.watching_reads {
+            self.record(HookEvent::Read { address, value });
+        }
+    }
+
+    /// Notes a write, if any hook is watching its address.
+    pub fn record_write(&self, address : u16, value : u8) {
+        if self.watching_writes {
+            self.record(HookEvent::Write { address, value });
+        }
+    }
+
+    /// Returns if any hook watches execution at an address.
+    pub fn watches_execute(&self, address : u16) -> bool {
+        self.hooks.iter().any(|hook| hook.trigger == Trigger::Execute(address))
+    }
+}
+
+/// Runs every hook matching an event.
+pub fn dispatch(mem : &mut GBMemory, event : HookEvent) {
+    // Take the hooks out, so callbacks can borrow memory
+    let mut hooks = replace(&mut mem.hooks.hooks, Vec::new());
+
+    for hook in &mut hooks {
+        if hook.trigger.matches(&event) {
+            (hook.callback)(mem, event);
+        }
+    }
+
+    // Keep any hooks added by the callbacks
+    let added = replace(&mut mem.hooks.hooks, hooks);
+    mem.hooks.hooks.extend(added);
+    mem.hooks.update_watches();
+}
+
+/// Runs hooks for memory accesses queued up since this was last called.
+pub fn dispatch_pending(mem : &mut GBMemory) {
+    if mem.hooks.pending.borrow().is_empty() {
+        return;
+    }
+
+    let pending : Vec<HookEvent> = mem.hooks.pending.borrow_mut().drain(..).collect();
+
+    for event in pending {
+        dispatch(mem, event);
+    }
+}
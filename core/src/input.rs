@@ -4,7 +4,7 @@
  * Handles input devices for the Gameboy.
 **/
 
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum GameboyButton {
     A, B,
     LEFT, RIGHT, UP, DOWN,
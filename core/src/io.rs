@@ -8,11 +8,23 @@ use cpu::interrupts::InterruptType;
 use mem::GBMemory;
 use gpu::GPUMode;
 
+use alloc::String;
+
+use events::CoreEvent;
+
+/// How many bytes sent over serial are kept, oldest first.
+const MAX_SERIAL_OUTPUT : usize = 4096;
+
+/// The start of a Game Boy Printer packet (magic bytes, then the command) which
+///  asks for the printer's buffer to be printed.
+const PRINT_COMMAND : [u8; 3] = [0x88, 0x33, 0x02];
+
 /// Storage for various I/O registers.
 #[derive(Serialize, Deserialize)]
 pub struct IORegisters {
     pub p1 : u8,    // 0x00 - Joypad info and controller (R/W)
     pub sb : u8,    // 0x01 - Serial transfer data (R/W)
+    pub sc : u8,    // 0x02 - Serial transfer control (R/W)
     pub div : u16,  // 0x04 - Divider register (R/W)
     pub tima : u8,  // 0x05 - Timer Counter (R/W)
     pub tma : u8,   // 0x06 - Timer Modulo (R/W)
@@ -49,6 +61,7 @@ impl IORegisters {
         return IORegisters {
             p1 : 0,
             sb : 0,
+            sc : 0,
             div : 0xABCC,
             tima : 0,
             tma : 0,
@@ -105,7 +118,8 @@ pub fn read(mem : &GBMemory, ptr : u8) -> u8 {
 
             output
         }
-        0x02 => mem.ioregs.sb,
+        0x01 => mem.ioregs.sb,
+        0x02 => mem.ioregs.sc | 0b01111110,
         0x04 => (mem.ioregs.div >> 8) as u8,
         0x05 => mem.ioregs.tima,
         0x06 => mem.ioregs.tma,
@@ -160,12 +174,8 @@ pub fn read(mem : &GBMemory, ptr : u8) -> u8 {
         0x49 => mem.gpu.obp1,
         0x4A => mem.gpu.wy,
         0x4B => mem.gpu.wx,
-        0x4C ... 0xFF => {
-            warn!("Out of range I/O register: {:02x}", ptr);
-            0xFF
-        },
         _ => {
-            warn!("Unknown I/O register: {:02x}", ptr);
+            report_unsupported_register(mem, ptr);
             0xFF
         }
     }
@@ -175,11 +185,15 @@ pub fn read(mem : &GBMemory, ptr : u8) -> u8 {
 pub fn write(mem : &mut GBMemory, ptr : u8, val : u8) {
     match ptr {
         0x00 => mem.ioregs.p1 = val,
-        0x01 => {
-            // Serial
-            //info!("{}", val as char);
+        0x01 => mem.ioregs.sb = val,
+        0x02 => {
+            mem.ioregs.sc = val;
+
+            // Transfer requested using the internal clock
+            if val & 0x81 == 0x81 {
+                execute_serial_transfer(mem);
+            }
         },
-        0x02 => mem.ioregs.sb = val,
         0x04 => mem.ioregs.div = 0,
         0x05 => mem.ioregs.tima = val,
         0x06 => mem.ioregs.tma = val,
@@ -215,9 +229,10 @@ pub fn write(mem : &mut GBMemory, ptr : u8, val : u8) {
             let changed_bit = val >> 7;
             if old_bit != changed_bit {
                 if changed_bit == 0 {
+                    // This can damage real hardware, but games (and fuzzers) still do it
                     if mem.gpu.mode != GPUMode::Vblank {
-                        panic!("Disabling/enabling LCD during non-vblank! (actual mode: {:?})",
-                               mem.gpu.mode);
+                        mem.events.report_once(CoreEvent::AccuracyWarning(
+                            String::from("LCD disabled outside of VBlank")));
                     }
 
                     mem.gpu.current_line = 0;
@@ -248,13 +263,38 @@ pub fn write(mem : &mut GBMemory, ptr : u8, val : u8) {
         0x49 => mem.gpu.obp1 = val,
         0x4A => mem.gpu.wy = val,
         0x4B => mem.gpu.wx = val,
-        0x4C ... 0xFF => {
-            warn!("Out of range I/O register: {:02x} = {:02x}", ptr, val);
-        },
-        _ => {
-            warn!("Unknown I/O register: {:02x} = {:02x}", ptr, val);
-        }
+        _ => report_unsupported_register(mem, ptr)
+    }
+}
+
+/// Reports an access to a register which isn't emulated (including those only
+///  present on the Game Boy Color).
+fn report_unsupported_register(mem : &GBMemory, ptr : u8) {
+    mem.events.report_once(CoreEvent::UnsupportedFeature(format!("I/O register FF{:02X}", ptr)));
+}
+
+/// Completes a serial transfer immediately. Nothing is ever connected, so the
+///  outgoing byte is recorded and 0xFF is received in exchange.
+fn execute_serial_transfer(mem : &mut GBMemory) {
+    mem.events.report_once(CoreEvent::LinkConnected);
+
+    if mem.serial_output.len() >= MAX_SERIAL_OUTPUT {
+        mem.serial_output.pop_front();
+    }
+    mem.serial_output.push_back(mem.ioregs.sb);
+
+    let printing = mem.serial_output.len() >= PRINT_COMMAND.len() &&
+        mem.serial_output.iter().rev().zip(PRINT_COMMAND.iter().rev()).all(|(x, y)| x == y);
+
+    if printing {
+        mem.events.push(CoreEvent::PrinterJobReady);
     }
+
+    mem.ioregs.sb = 0xFF;
+    mem.ioregs.sc &= 0x7F;
+
+    mem.ioregs.iflag |= 1 << (InterruptType::SERIAL as u8);
+    mem.dirty_interrupts = true;
 }
 
 /// Executes a DMA.
@@ -262,8 +302,12 @@ fn execute_dma(mem : &mut GBMemory) {
     // TODO: Locking
     let address = (mem.ioregs.dma as u16) * 0x100;
 
+    mem.trace_begin("OAM DMA", "mmu");
+
     for i in 0 .. 0xA0 {
         let byte = mem.read(address + i);
         mem.write(0xFE00 + i, byte);
     }
+
+    mem.trace_end("OAM DMA", "mmu");
 }
@@ -12,10 +12,20 @@ extern crate serde_derive;
 extern crate serde;
 
 pub mod rom;
+pub mod cheats;
 pub mod mem;
 pub mod cpu;
 pub mod gpu;
 pub mod input;
 pub mod sound;
+pub mod debug;
+pub mod achievements;
+pub mod hooks;
+pub mod profiler;
+pub mod trace;
+pub mod report;
+pub mod events;
+pub mod audit;
 
 mod io;
+mod encode;
@@ -18,7 +18,21 @@ use io::IORegisters;
 
 use sound::Sound;
 
+#[cfg(feature = "profiling")]
+use profiler::Profiler;
+use profiler::Subsystem;
+
+use hooks::Hooks;
+
+use debug::coverage::CodeCoverage;
+
+use trace::Tracer;
+
+use events::CoreEvent;
+use events::EventQueue;
+
 use alloc::Vec;
+use alloc::VecDeque;
 
 #[derive(Serialize, Deserialize)]
 pub struct GBMemory {
@@ -33,11 +47,48 @@ pub struct GBMemory {
     pub ioregs : IORegisters,
 
     pub buttons : GameboyInput,
+
+    /// Bytes sent over the serial port, which test ROMs use to report results.
+    ///  Frontends should drain this with `take_serial_output` each frame.
+    #[serde(skip)]
+    pub serial_output : VecDeque<u8>,
+
+    /// If set, time spent in each subsystem is measured.
+    #[cfg(feature = "profiling")]
+    #[serde(skip)]
+    pub profiler : Option<Profiler>,
+
+    /// If set, events are recorded for viewing on a timeline.
+    #[serde(skip)]
+    pub tracer : Option<Tracer>,
+
+    /// If set, executed instructions are recorded.
+    #[serde(skip)]
+    pub coverage : Option<CodeCoverage>,
+
+    /// Callbacks for automation. These aren't kept in save states.
+    #[serde(skip)]
+    pub hooks : Hooks,
+
+    /// Events for the frontend to show the user.
+    #[serde(skip)]
+    pub events : EventQueue,
+
+    /// If cartridge RAM was written to this frame.
+    #[serde(skip)]
+    save_ram_written : bool
 }
 
 impl GBMemory {
     /// Reads a value from memory. 0xFF if invalid.
     pub fn read(&self, ptr : u16) -> u8 {
+        let result = self.read_unhooked(ptr);
+        self.hooks.record_read(ptr, result);
+        return result;
+    }
+
+    /// Reads a value from memory without notifying hooks.
+    fn read_unhooked(&self, ptr : u16) -> u8 {
         let result = match ptr {
             0xFFFF => { // Interrupt enable reg
                 self.interrupt_reg
@@ -46,7 +97,11 @@ impl GBMemory {
                 self.high_ram[(ptr - 0xFF80) as usize]
             }
             0xFF00 ... 0xFF7F => { // I/O Registers
-                io::read(self, (ptr & 0xFF) as u8)
+                self.profile_count(Subsystem::Mmu, 1);
+                let previous = self.profile_enter(Subsystem::Mmu);
+                let value = io::read(self, (ptr & 0xFF) as u8);
+                self.profile_exit(previous);
+                value
             }
             0xFEA0 ... 0xFEFF => { // Unusable
                 //println!("WARN: Reading from unreadable memory: {:04x}", ptr);
@@ -95,10 +150,22 @@ impl GBMemory {
         return result;
     }
 
+    /// Reads a value from memory for debugging purposes, ignoring restrictions
+    ///  which the GPU places on VRAM/OAM access.
+    pub fn debug_read(&self, ptr : u16) -> u8 {
+        match ptr {
+            0xFE00 ... 0xFE9F => self.gpu.oam[(ptr - 0xFE00) as usize],
+            0x8000 ... 0x9FFF => self.gpu.vram[(ptr - 0x8000) as usize],
+            _ => self.read_unhooked(ptr)
+        }
+    }
+
     /// Writes a value to a memory location if possible.
     pub fn write(&mut self, ptr : u16, val : u8) {
         //println!("${:04X}: Write ${:02X}", ptr, val);
 
+        self.hooks.record_write(ptr, val);
+
         match ptr {
             0xFFFF => { // Interrupt enable reg
                 self.interrupt_reg = val;
@@ -107,7 +174,10 @@ impl GBMemory {
                 self.high_ram[(ptr - 0xFF80) as usize] = val;
             }
             0xFF00 ... 0xFF7F => { // I/O Registers
+                self.profile_count(Subsystem::Mmu, 1);
+                let previous = self.profile_enter(Subsystem::Mmu);
                 io::write(self, (ptr & 0xFF) as u8, val);
+                self.profile_exit(previous);
             }
             0xFEA0 ... 0xFEFF => { // Unusable
                 //println!("WARN: Writing to unreadable memory: {:04x} = {:02x}", ptr, val);
@@ -130,6 +200,11 @@ impl GBMemory {
             }
             0xA000 ... 0xBFFF => { // Switchable RAM
                 self.rom.write_ram(ptr - 0xA000, val);
+
+                if !self.save_ram_written && !self.rom.cart_ram.is_empty() {
+                    self.save_ram_written = true;
+                    self.events.push(CoreEvent::SaveRamWritten);
+                }
             }
             0x8000 ... 0x9FFF => { // GPU
                 // Check if write is valid
@@ -143,7 +218,13 @@ impl GBMemory {
                 };
             }
             0x0000 ... 0x7FFF => { // Cartridge / Switchable ROM
-                self.rom.write(ptr, val);
+                let previous_bank = self.rom.current_bank();
+                self.rom.write(ptr, val, &self.events);
+
+                let bank = self.rom.current_bank();
+                if bank != previous_bank {
+                    self.trace_instant("Bank switch", "mmu", Some(("bank", bank as u32)));
+                }
             }
             _ => {
                 panic!("Programmer error: {:04x} = {:02x} was not matched!", ptr, val);
@@ -151,15 +232,111 @@ impl GBMemory {
         }
     }
 
+    /// Called at the start of each VBlank.
+    pub fn end_frame(&mut self) {
+        self.apply_ram_cheats();
+        self.save_ram_written = false;
+    }
+
+    /// Applies any enabled GameShark codes, as the cartridge would at VBlank.
+    pub fn apply_ram_cheats(&mut self) {
+        for i in 0 .. self.rom.cheats.cheats().len() {
+            if let Some((address, value)) = self.rom.cheats.ram_write(i) {
+                if self.read_unhooked(address) != value {
+                    let code = self.rom.cheats.cheats()[i].code.clone();
+                    self.events.report_once(CoreEvent::CheatFired(code));
+                }
+
+                self.write(address, value);
+            }
+        }
+    }
+
     /// Reads a short. 0xFFFF if invalid.
     pub fn read_short(&self, ptr : u16) -> u16 {
-        return (self.read(ptr) as u16) | ((self.read(ptr + 1) as u16) << 8);
+        return (self.read(ptr) as u16) | ((self.read(ptr.wrapping_add(1)) as u16) << 8);
     }
 
     /// Writes a short value to a memory location if possible.
     pub fn write_short(&mut self, ptr : u16, val : u16) {
         self.write(ptr, (val & 0xFF) as u8);
-        self.write(ptr + 1, ((val >> 8) & 0xFF) as u8);
+        self.write(ptr.wrapping_add(1), ((val >> 8) & 0xFF) as u8);
+    }
+
+    /// Starts a span on the trace timeline, if tracing.
+    pub fn trace_begin(&self, name : &'static str, category : &'static str) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.begin(name, category);
+        }
+    }
+
+    /// Finishes a span started by `trace_begin`.
+    pub fn trace_end(&self, name : &'static str, category : &'static str) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.end(name, category);
+        }
+    }
+
+    /// Records a single point in time on the trace timeline, if tracing.
+    pub fn trace_instant(&self, name : &'static str, category : &'static str,
+                         arg : Option<(&'static str, u32)>) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.instant(name, category, arg);
+        }
+    }
+
+    /// Starts charging time to a subsystem, if profiling. The result should be
+    ///  passed to `profile_exit` once the subsystem is done.
+    #[cfg(feature = "profiling")]
+    pub fn profile_enter(&self, subsystem : Subsystem) -> Option<Subsystem> {
+        match self.profiler {
+            Some(ref profiler) => profiler.enter(subsystem),
+            None => None
+        }
+    }
+
+    /// Returns to charging time to whatever was running before `profile_enter`.
+    #[cfg(feature = "profiling")]
+    pub fn profile_exit(&self, previous : Option<Subsystem>) {
+        if let Some(ref profiler) = self.profiler {
+            profiler.exit(previous);
+        }
+    }
+
+    /// Notes that an instruction is being executed, if recording coverage.
+    pub fn record_execution(&mut self, address : u16) {
+        if let Some(ref mut coverage) = self.coverage {
+            coverage.record(address, self.rom.current_bank());
+        }
+    }
+
+    /// Counts work done by a subsystem, if profiling.
+    #[cfg(feature = "profiling")]
+    pub fn profile_count(&self, subsystem : Subsystem, amount : u64) {
+        if let Some(ref profiler) = self.profiler {
+            profiler.count(subsystem, amount);
+        }
+    }
+
+    // Without profiling, these compile away to nothing
+
+    #[cfg(not(feature = "profiling"))]
+    #[inline(always)]
+    pub fn profile_enter(&self, _ : Subsystem) -> Option<Subsystem> {
+        None
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    #[inline(always)]
+    pub fn profile_exit(&self, _ : Option<Subsystem>) {}
+
+    #[cfg(not(feature = "profiling"))]
+    #[inline(always)]
+    pub fn profile_count(&self, _ : Subsystem, _ : u64) {}
+
+    /// Drains everything sent over the serial port so far.
+    pub fn take_serial_output(&mut self) -> Vec<u8> {
+        self.serial_output.drain(..).collect()
     }
 
     /// Sets the input registers.
@@ -183,7 +360,18 @@ impl GBMemory {
             buttons : GameboyInput {
                 p14 : 0,
                 p15 : 0
-            }
+            },
+
+            serial_output : VecDeque::new(),
+
+            #[cfg(feature = "profiling")]
+            profiler : None,
+            tracer : None,
+
+            coverage : None,
+            hooks : Hooks::default(),
+            events : EventQueue::default(),
+            save_ram_written : false
         }
     }
 }
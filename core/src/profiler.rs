@@ -0,0 +1,134 @@
+/**
+ * profiler.rs
+ *
+ * Optional measurement of where emulation time goes. The core has no clock of its
+ *  own, so one is supplied by the host. Profiling is only compiled in with the
+ *  "profiling" feature.
+**/
+
+use core::cell::Cell;
+
+/// Parts of the system which time can be charged to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Subsystem {
+    /// Instruction dispatch, interrupts and timers.
+    Cpu,
+    /// Rendering and LCD timing.
+    Ppu,
+    /// Sound generation and mixing.
+    Apu,
+    /// I/O register handling, including DMA.
+    Mmu
+}
+
+pub const SUBSYSTEMS : [Subsystem; 4] = [Subsystem::Cpu, Subsystem::Ppu,
+                                         Subsystem::Apu, Subsystem::Mmu];
+
+impl Subsystem {
+    fn index(&self) -> usize {
+        match *self {
+            Subsystem::Cpu => 0,
+            Subsystem::Ppu => 1,
+            Subsystem::Apu => 2,
+            Subsystem::Mmu => 3
+        }
+    }
+
+    /// Returns a short, human-readable name for this subsystem.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Subsystem::Cpu => "CPU",
+            Subsystem::Ppu => "PPU",
+            Subsystem::Apu => "APU",
+            Subsystem::Mmu => "MMU"
+        }
+    }
+
+    /// Returns what work done by this subsystem is counted in.
+    pub fn unit(&self) -> &'static str {
+        match *self {
+            Subsystem::Cpu => "instructions",
+            Subsystem::Ppu => "lines",
+            Subsystem::Apu => "samples",
+            Subsystem::Mmu => "accesses"
+        }
+    }
+}
+
+/// Charges elapsed time to whichever subsystem is currently running. Only one
+///  subsystem is charged at a time, so nested subsystems (such as I/O accesses
+///  made by an instruction) aren't counted twice.
+///
+/// Work done by each subsystem is counted too: instructions executed by the CPU,
+///  scanlines finished by the PPU, stereo samples produced by the APU and
+///  register accesses for the MMU.
+pub struct Profiler {
+    /// Returns the current time in arbitrary (but consistent) units.
+    clock : fn() -> u64,
+    current : Cell<Option<Subsystem>>,
+    since : Cell<u64>,
+    totals : [Cell<u64>; 4],
+    counts : [Cell<u64>; 4]
+}
+
+impl Profiler {
+    /// Charges time so far to the current subsystem, and switches to another.
+    fn switch(&self, next : Option<Subsystem>) -> Option<Subsystem> {
+        let now = (self.clock)();
+
+        if let Some(current) = self.current.get() {
+            let total = &self.totals[current.index()];
+            total.set(total.get() + now.wrapping_sub(self.since.get()));
+        }
+
+        self.since.set(now);
+        self.current.replace(next)
+    }
+
+    /// Starts charging time to a subsystem. Returns the subsystem which was
+    ///  running before, which should be passed to `exit`.
+    pub fn enter(&self, subsystem : Subsystem) -> Option<Subsystem> {
+        self.switch(Some(subsystem))
+    }
+
+    /// Returns to charging time to the previous subsystem (or to nothing).
+    pub fn exit(&self, previous : Option<Subsystem>) {
+        self.switch(previous);
+    }
+
+    /// Returns the total time charged to a subsystem, in the clock's units.
+    pub fn total(&self, subsystem : Subsystem) -> u64 {
+        self.totals[subsystem.index()].get()
+    }
+
+    /// Counts work done by a subsystem.
+    pub fn count(&self, subsystem : Subsystem, amount : u64) {
+        let count = &self.counts[subsystem.index()];
+        count.set(count.get().wrapping_add(amount));
+    }
+
+    /// Returns the work counted for a subsystem.
+    pub fn counted(&self, subsystem : Subsystem) -> u64 {
+        self.counts[subsystem.index()].get()
+    }
+
+    /// Clears all totals and counts.
+    pub fn reset(&self) {
+        for value in self.totals.iter().chain(self.counts.iter()) {
+            value.set(0);
+        }
+    }
+
+    /// Builds a profiler.
+    ///
+    /// * `clock` - Returns the current time, such as in nanoseconds.
+    pub fn build(clock : fn() -> u64) -> Profiler {
+        Profiler {
+            clock,
+            current : Cell::new(None),
+            since : Cell::new(0),
+            totals : [Cell::new(0), Cell::new(0), Cell::new(0), Cell::new(0)],
+            counts : [Cell::new(0), Cell::new(0), Cell::new(0), Cell::new(0)]
+        }
+    }
+}
@@ -0,0 +1,65 @@
+/**
+ * report.rs
+ *
+ * Reports how much memory a running system is using, for budgeting on
+ *  targets with little RAM.
+**/
+
+use serde::Serialize;
+
+use cpu::CPU;
+
+use encode::encode;
+use encode::ByteCounter;
+
+/// Heap memory used by a system, in bytes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MemoryReport {
+    /// The RGB frame the PPU renders into.
+    pub framebuffer : usize,
+    /// VRAM and OAM.
+    pub video_ram : usize,
+    /// Work RAM and high RAM.
+    pub work_ram : usize,
+    pub cart_ram : usize,
+    /// The ROM, if it was copied onto the heap. Borrowed ROMs take no heap memory.
+    pub rom_copy : usize,
+    /// The APU's sample buffer.
+    pub audio_buffers : usize,
+    /// Debugging aids which are normally empty - serial output, cheats and hooks.
+    pub other : usize,
+    /// The size of a save state, as encoded by bincode's default configuration
+    ///  (which the libretro frontend uses). Save states never include the ROM.
+    pub savestate : usize
+}
+
+impl MemoryReport {
+    /// Returns the total heap memory used by the system, excluding save states.
+    pub fn total(&self) -> usize {
+        self.framebuffer + self.video_ram + self.work_ram + self.cart_ram + self.rom_copy
+            + self.audio_buffers + self.other
+    }
+}
+
+/// Builds a report of the memory a system is using.
+pub fn memory_report(cpu : &CPU) -> MemoryReport {
+    let mem = &cpu.mem;
+
+    MemoryReport {
+        framebuffer : mem.gpu.pixel_data.capacity(),
+        video_ram : mem.gpu.vram.capacity() + mem.gpu.oam.capacity(),
+        work_ram : mem.ram.capacity() + mem.high_ram.capacity(),
+        cart_ram : mem.rom.cart_ram.capacity(),
+        rom_copy : mem.rom.heap_size(),
+        audio_buffers : mem.sound.buffer_size(),
+        other : mem.serial_output.capacity() + mem.rom.cheats.heap_size() + mem.hooks.heap_size(),
+        savestate : serialized_size(cpu)
+    }
+}
+
+/// Returns how many bytes bincode would encode a value to.
+fn serialized_size<T : Serialize>(value : &T) -> usize {
+    let mut counter = ByteCounter(0);
+    encode(value, &mut counter);
+    counter.0
+}
@@ -6,6 +6,14 @@
 
 use alloc::String;
 use alloc::Vec;
+use alloc::borrow::Cow;
+
+use core::mem;
+
+use cheats::CheatManager;
+
+use events::CoreEvent;
+use events::EventQueue;
 
 /// The different kinds of cartridges that can be handled. Each has a
 ///  specific way of managing memory/providing additional capabilities.
@@ -40,25 +48,124 @@ pub enum CartridgeType {
     HudsonHuC1        = 0xFF
 }
 
+impl CartridgeType {
+    /// Parses the cartridge type byte in a ROM's header.
+    pub fn from_id(id : u8) -> Option<CartridgeType> {
+        return match id {
+            0x00 => Some(CartridgeType::RomOnly),
+            0x01 => Some(CartridgeType::RomMbc1),
+            0x02 => Some(CartridgeType::RomMbc1Ram),
+            0x03 => Some(CartridgeType::RomMbc1RamBatt),
+            0x05 => Some(CartridgeType::RomMbc2),
+            0x06 => Some(CartridgeType::RomMbc2Batt),
+            0x08 => Some(CartridgeType::RomRam),
+            0x09 => Some(CartridgeType::RomRamBatt),
+            0x0B => Some(CartridgeType::RomMMMD1),
+            0x0C => Some(CartridgeType::RomMMMD1Sram),
+            0x0D => Some(CartridgeType::RomMMMD1SramBatt),
+            0x0F => Some(CartridgeType::RomMbc3TimerBatt),
+            0x10 => Some(CartridgeType::RomMbc3TimerRamBatt),
+            0x11 => Some(CartridgeType::RomMbc3),
+            0x12 => Some(CartridgeType::RomMbc3Ram),
+            0x13 => Some(CartridgeType::RomMbc3RamBatt),
+            0x19 => Some(CartridgeType::RomMbc5),
+            0x1A => Some(CartridgeType::RomMbc5Ram),
+            0x1B => Some(CartridgeType::RomMbc5RamBatt),
+            0x1C => Some(CartridgeType::RomMbc5Rumble),
+            0x1D => Some(CartridgeType::RomMbc5RumbleSram),
+            0x1E => Some(CartridgeType::RomMbc5RumbleSramBatt),
+            0xFC => Some(CartridgeType::PocketCamera),
+            0xFD => Some(CartridgeType::BandaiTAMA5),
+            0xFE => Some(CartridgeType::HudsonHuC3),
+            0xFF => Some(CartridgeType::HudsonHuC1),
+            _    => None
+        }
+    }
+
+    /// Returns if this cartridge has a mapper implementation.
+    pub fn is_supported(&self) -> bool {
+        return match *self {
+            CartridgeType::RomOnly |
+            CartridgeType::RomMbc1 |
+            CartridgeType::RomMbc1Ram |
+            CartridgeType::RomMbc1RamBatt |
+            CartridgeType::RomMbc2 |
+            CartridgeType::RomMbc2Batt |
+            CartridgeType::RomMbc3RamBatt |
+            CartridgeType::RomMbc3TimerRamBatt => true,
+            _ => false
+        }
+    }
+
+    /// Returns if this cartridge has battery-backed RAM which should persist.
+    pub fn has_battery(&self) -> bool {
+        return match *self {
+            CartridgeType::RomMbc1RamBatt |
+            CartridgeType::RomMbc2Batt |
+            CartridgeType::RomRamBatt |
+            CartridgeType::RomMMMD1SramBatt |
+            CartridgeType::RomMbc3TimerBatt |
+            CartridgeType::RomMbc3TimerRamBatt |
+            CartridgeType::RomMbc3RamBatt |
+            CartridgeType::RomMbc5RamBatt |
+            CartridgeType::RomMbc5RumbleSramBatt => true,
+            _ => false
+        }
+    }
+}
+
+/// Reasons why a ROM could not be loaded.
+#[derive(PartialEq, Debug)]
+pub enum RomError {
+    /// The data is too small to contain a cartridge header.
+    TooSmall,
+    UnknownCartridgeType(u8),
+    UnsupportedCartridgeType(CartridgeType),
+    UnknownRomSize(u8),
+    UnknownRamSize(u8)
+}
+
+/// The end of the cartridge header.
+const HEADER_END : usize = 0x150;
+
 /// Holds a game's ROM, and exposes interfaces to read information from
 ///  it intelligently.
 #[derive(Serialize, Deserialize)]
 pub struct GameROM {
-    backing_data : Vec<u8>,
+    /// Either a copy of the ROM, or borrowed from the host to save memory. Save
+    ///  states don't include this - the host attaches it again after loading one.
+    #[serde(skip)]
+    backing_data : Cow<'static, [u8]>,
     current_bank : u8,
 
     pub cart_ram : Vec<u8>,
     ram_size : usize,
 
     pub name : String,
-    pub cart_type : CartridgeType
+    pub cart_type : CartridgeType,
+
+    /// Header fields, read when the cartridge is built, as the data may not be
+    ///  attached after loading a save state.
+    header_checksum : u8,
+    global_checksum : u16,
+    supports_cgb : bool,
+
+    /// Cheats patching reads from this cartridge.
+    #[serde(skip)]
+    pub cheats : CheatManager
 }
 
 impl GameROM {
     pub fn read(&self, ptr : u16) -> u8 {
+        return self.cheats.patch_rom_read(ptr, self.read_mapped(ptr));
+    }
+
+    /// Reads a value from the cartridge through its mapper, before any cheats.
+    fn read_mapped(&self, ptr : u16) -> u8 {
         return match self.cart_type {
+            // Truncated ROMs read as open bus
             CartridgeType::RomOnly => {
-                self.backing_data[ptr as usize]
+                self.backing_data.get(ptr as usize).cloned().unwrap_or(0xFF)
             }
             CartridgeType::RomMbc1 |
             CartridgeType::RomMbc1Ram |
@@ -68,7 +175,7 @@ impl GameROM {
             CartridgeType::RomMbc3RamBatt |
             CartridgeType::RomMbc3TimerRamBatt => {
                 if ptr < 0x4000 {
-                    self.backing_data[ptr as usize]
+                    self.backing_data.get(ptr as usize).cloned().unwrap_or(0xFF)
                 } else {
                     let target = ptr as usize + (self.current_bank as usize - 1)
                         * 0x4000;
@@ -92,10 +199,13 @@ impl GameROM {
             return 0xFF;
         }
 
-        return self.cart_ram[ptr as usize];
+        // RAM banking isn't supported, and smaller RAM chips don't fill the area
+        return self.cart_ram.get(ptr as usize).cloned().unwrap_or(0xFF);
     }
 
-    pub fn write(&mut self, ptr : u16, val : u8) {
+    /// Writes to the cartridge's mapper. Anything the mapper doesn't support is
+    ///  reported to `events`.
+    pub fn write(&mut self, ptr : u16, val : u8, events : &EventQueue) {
         match self.cart_type {
             CartridgeType::RomOnly => {
                 //println!("WARN: Writing to ROM: {:04x} = {:02x}", ptr, val);
@@ -114,11 +224,12 @@ impl GameROM {
                         }
                     }
                     0x6000 ... 0x7FFF => { // Memory models
-                        warn!("MBC1 memory models are not supported!");
+                        events.report_once(CoreEvent::UnsupportedFeature(
+                            String::from("MBC memory models")));
                     }
                     _ => {
-                        warn!("Attempted to write to ROM+MBC1 cartridge @ {:04x} = {:02x}",
-                                 ptr, val);
+                        events.report_once(CoreEvent::UnsupportedFeature(
+                            String::from("MBC RAM banking")));
                     }
 
                 }
@@ -131,7 +242,8 @@ impl GameROM {
                     }
                     0x2000 ... 0x3FFF => { // Bank switching
                         if (ptr >> 8) & 0x1 != 1 {
-                            warn!("MBC2: Invalid bank switch command!");
+                            events.report_once(CoreEvent::AccuracyWarning(
+                                String::from("Invalid MBC2 bank switch")));
                         } else {
                             self.current_bank = val & 0b1111;
                             if self.current_bank < 1 {
@@ -139,14 +251,10 @@ impl GameROM {
                             }
                         }
                     }
-                    0x6000 ... 0x7FFF => { // Memory models
-                        warn!("MBC1 memory models are not supported!");
-                    }
                     _ => {
-                        warn!("Attempted to write to ROM+MBC1 cartridge @ {:04x} = {:02x}",
-                                 ptr, val);
+                        // MBC2 has no RAM banking or memory models - hardware
+                        //  ignores these writes
                     }
-
                 }
             },
             CartridgeType::RomMbc3RamBatt |
@@ -162,11 +270,12 @@ impl GameROM {
                         }
                     }
                     0x6000 ... 0x7FFF => { // Memory models
-                        warn!("MBC1 memory models are not supported!");
+                        events.report_once(CoreEvent::UnsupportedFeature(
+                            String::from("MBC memory models")));
                     }
                     _ => {
-                        warn!("Attempted to write to ROM+MBC1 cartridge @ {:04x} = {:02x}",
-                                 ptr, val);
+                        events.report_once(CoreEvent::UnsupportedFeature(
+                            String::from("MBC RAM banking")));
                     }
 
                 }
@@ -177,13 +286,67 @@ impl GameROM {
         }
     }
 
+    /// Returns if the ROM data is borrowed from the host, rather than copied.
+    pub fn is_borrowed(&self) -> bool {
+        return match self.backing_data {
+            Cow::Borrowed(_) => true,
+            Cow::Owned(_) => false
+        }
+    }
+
+    /// Returns how much heap memory the ROM data uses, in bytes.
+    pub fn heap_size(&self) -> usize {
+        return match self.backing_data {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(ref data) => data.capacity()
+        }
+    }
+
+    /// Returns the raw ROM data.
+    pub fn data(&self) -> &[u8] {
+        return &self.backing_data;
+    }
+
+    /// Takes the ROM data out of this cartridge, leaving it empty.
+    pub fn take_data(&mut self) -> Cow<'static, [u8]> {
+        return mem::replace(&mut self.backing_data, Cow::Borrowed(&[]));
+    }
+
+    /// Attaches ROM data to a cartridge loaded from a save state, which doesn't
+    ///  include it. The data should be from the same game.
+    pub fn attach_data(&mut self, data : Cow<'static, [u8]>) {
+        self.backing_data = data;
+    }
+
+    /// Returns the ROM bank currently mapped at 0x4000 - 0x7FFF.
+    pub fn current_bank(&self) -> u8 {
+        return self.current_bank;
+    }
+
+    /// Returns the header checksum (0x14D), which covers the title and header flags.
+    pub fn header_checksum(&self) -> u8 {
+        return self.header_checksum;
+    }
+
+    /// Returns the global checksum (0x14E - 0x14F) over the entire ROM.
+    pub fn global_checksum(&self) -> u16 {
+        return self.global_checksum;
+    }
+
+    /// Returns if the cartridge declares itself as Gameboy Color compatible.
+    pub fn supports_cgb(&self) -> bool {
+        return self.supports_cgb;
+    }
+
     pub fn write_ram(&mut self, ptr : u16, val : u8) {
         if self.ram_size == 0 {
             warn!("Writing to RAM on a ROM-only cartridge!");
             return;
         }
 
-        self.cart_ram[ptr as usize] = val;
+        if let Some(value) = self.cart_ram.get_mut(ptr as usize) {
+            *value = val;
+        }
     }
 
     /// Builds a new ROM from the specified file. Expects
@@ -191,85 +354,125 @@ impl GameROM {
     ///
     /// * `data` - The data to build a ROM from.
     pub fn build(data : Vec<u8>) -> GameROM {
-        let rom_size = get_rom_size(data[0x148]);
+        return match GameROM::try_build(data) {
+            Ok(rom) => rom,
+            Err(error) => panic!("Unable to load ROM: {:?}", error)
+        }
+    }
+
+    /// Builds a new ROM from the specified file, validating its header first.
+    ///
+    /// * `data` - The data to build a ROM from.
+    pub fn try_build(data : Vec<u8>) -> Result<GameROM, RomError> {
+        return GameROM::try_build_from(Cow::Owned(data));
+    }
+
+    /// Builds a new ROM which reads from data owned by the host, without copying
+    ///  it - such as a ROM stored in flash.
+    ///
+    /// * `data` - The data to build a ROM from.
+    pub fn try_build_borrowed(data : &'static [u8]) -> Result<GameROM, RomError> {
+        return GameROM::try_build_from(Cow::Borrowed(data));
+    }
+
+    fn try_build_from(data : Cow<'static, [u8]>) -> Result<GameROM, RomError> {
+        if data.len() < HEADER_END {
+            return Err(RomError::TooSmall);
+        }
+
+        let rom_size = match try_get_rom_size(data[0x148]) {
+            Some(value) => value,
+            None => return Err(RomError::UnknownRomSize(data[0x148]))
+        };
 
         if rom_size != data.len() {
             warn!("File size is not equal to what ROM declares!");
         }
 
-        let name = String::from_utf8(data[0x134 .. 0x142].to_vec()).unwrap();
-        let cart_type = match data[0x0147] {
-            0x00 => CartridgeType::RomOnly,
-            0x01 => CartridgeType::RomMbc1,
-            0x02 => CartridgeType::RomMbc1Ram,
-            0x03 => CartridgeType::RomMbc1RamBatt,
-            0x05 => CartridgeType::RomMbc2,
-            0x06 => CartridgeType::RomMbc2Batt,
-            0x08 => CartridgeType::RomRam,
-            0x09 => CartridgeType::RomRamBatt,
-            0x0B => CartridgeType::RomMMMD1,
-            0x0C => CartridgeType::RomMMMD1Sram,
-            0x0D => CartridgeType::RomMMMD1SramBatt,
-            0x0F => CartridgeType::RomMbc3TimerBatt,
-            0x10 => CartridgeType::RomMbc3TimerRamBatt,
-            0x11 => CartridgeType::RomMbc3 ,
-            0x12 => CartridgeType::RomMbc3Ram,
-            0x13 => CartridgeType::RomMbc3RamBatt,
-            0x19 => CartridgeType::RomMbc5,
-            0x1A => CartridgeType::RomMbc5Ram,
-            0x1B => CartridgeType::RomMbc5RamBatt,
-            0x1C => CartridgeType::RomMbc5Rumble,
-            0x1D => CartridgeType::RomMbc5RumbleSram,
-            0x1E => CartridgeType::RomMbc5RumbleSramBatt,
-            0xFC => CartridgeType::PocketCamera,
-            0xFD => CartridgeType::BandaiTAMA5,
-            0xFE => CartridgeType::HudsonHuC3,
-            0xFF => CartridgeType::HudsonHuC1,
-            _    => panic!("Unknown cartridge type: {:02x}", data[0x0147])
+        let cart_type = match CartridgeType::from_id(data[0x0147]) {
+            Some(value) => value,
+            None => return Err(RomError::UnknownCartridgeType(data[0x0147]))
+        };
+
+        if !cart_type.is_supported() {
+            return Err(RomError::UnsupportedCartridgeType(cart_type));
+        }
+
+        let ram_size = match try_get_ram_size(data[0x149]) {
+            Some(value) => value,
+            None => return Err(RomError::UnknownRamSize(data[0x149]))
         };
-        let ram_size = get_ram_size(data[0x149]);
+
+        // Titles are padded with NULs, and aren't guaranteed to be valid text
+        let name = String::from(String::from_utf8_lossy(&data[0x134 .. 0x142])
+            .trim_right_matches('\0'));
+
+        let header_checksum = data[0x14D];
+        let global_checksum = ((data[0x14E] as u16) << 8) | (data[0x14F] as u16);
+        let supports_cgb = data[0x143] & 0x80 == 0x80;
 
         let ram = vec![0xFF; ram_size];
 
         debug!("Allocated {} bytes of cart RAM", ram.len());
 
-        return GameROM {
+        return Ok(GameROM {
             backing_data : data,
             name,
             cart_type,
+            header_checksum,
+            global_checksum,
+            supports_cgb,
             current_bank : 1,
 
             cart_ram : ram,
-            ram_size
-        };
+            ram_size,
+
+            cheats : CheatManager::default()
+        });
     }
 }
 
 /// Returns a ROM size for a particular ROM id.
 pub fn get_rom_size(id : u8) -> usize {
+    return match try_get_rom_size(id) {
+        Some(value) => value,
+        None => panic!("Unknown ROM size: {}", id)
+    }
+}
+
+/// Returns a ROM size for a particular ROM id, if it is known.
+pub fn try_get_rom_size(id : u8) -> Option<usize> {
     return match id {
-        0    => 32   * 1024, // 32  Kbyte
-        1    => 64   * 1024, // 64  Kbyte
-        2    => 128  * 1024, // 128 Kbyte
-        3    => 256  * 1024, // 256 Kbyte
-        4    => 512  * 1024, // 512 Kbyte
-        5    => 1024 * 1024, // 1   Mbyte
-        6    => 2048 * 1024, // 2   Mbyte
-        0x52 => 1152 * 1024, // 1.1 Mbyte
-        0x53 => 1280 * 1024, // 1.2 Mbyte
-        0x54 => 1536 * 1024, // 1.5 Mbyte
-        _    => panic!("Unknown ROM size: {}", id)
+        0    => Some(32   * 1024), // 32  Kbyte
+        1    => Some(64   * 1024), // 64  Kbyte
+        2    => Some(128  * 1024), // 128 Kbyte
+        3    => Some(256  * 1024), // 256 Kbyte
+        4    => Some(512  * 1024), // 512 Kbyte
+        5    => Some(1024 * 1024), // 1   Mbyte
+        6    => Some(2048 * 1024), // 2   Mbyte
+        0x52 => Some(1152 * 1024), // 1.1 Mbyte
+        0x53 => Some(1280 * 1024), // 1.2 Mbyte
+        0x54 => Some(1536 * 1024), // 1.5 Mbyte
+        _    => None
     }
 }
 
 /// Returns a RAM size for a particular RAM id.
 pub fn get_ram_size(id : u8) -> usize {
+    return match try_get_ram_size(id) {
+        Some(value) => value,
+        None => panic!("Unknown RAM size: {}", id)
+    }
+}
+
+/// Returns a RAM size for a particular RAM id, if it is known.
+pub fn try_get_ram_size(id : u8) -> Option<usize> {
     return match id {
-        0 => 0,          // ROM only
-        1 => 2   * 1024, // 2  Kbyte
-        2 => 8   * 1024, // 8  Kbyte
-        3 => 32  * 1024, // 32 Kbyte
-        4 => 128 * 1024,  // 128 Kbyte,
-        _ => panic!("Unknown RAM size: {}", id)
+        0 => Some(0),          // ROM only
+        1 => Some(2   * 1024), // 2  Kbyte
+        2 => Some(8   * 1024), // 8  Kbyte
+        3 => Some(32  * 1024), // 32 Kbyte
+        4 => Some(128 * 1024), // 128 Kbyte
+        _ => None
     }
 }
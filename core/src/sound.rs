@@ -6,6 +6,8 @@ use io::IORegisters;
 
 use alloc::Vec;
 
+use core::mem::size_of;
+
 // TODO: Vary this on different platforms?
 const SOUND_CPU_SPEED : u32 = 4194304;
 
@@ -296,6 +298,16 @@ impl Sound {
         (samples, old_pointer)
     }
 
+    /// Returns how many stereo samples are waiting to be taken.
+    pub fn pending_samples(&self) -> usize {
+        self.sample_pointer / 2
+    }
+
+    /// Returns the size of the sample buffer, in bytes.
+    pub fn buffer_size(&self) -> usize {
+        self.samples.capacity() * size_of::<f32>()
+    }
+
     pub fn build() -> Sound {
         Sound {
             channel_1_running : false,
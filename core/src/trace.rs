@@ -0,0 +1,118 @@
+/**
+ * trace.rs
+ *
+ * Optional recording of what the system is doing over time - frames, interrupts,
+ *  bank switches and DMA - for viewing on a timeline. As with the profiler, the
+ *  core has no clock of its own, so one is supplied by the host.
+**/
+
+use alloc::Vec;
+
+use core::cell::Cell;
+use core::cell::RefCell;
+
+/// Events kept before new ones are dropped, if the host doesn't take them. Spans
+///  are dropped whole, so a span which was begun is always ended.
+const MAX_EVENTS : usize = 1 << 16;
+
+/// Where a trace event sits on the timeline.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TraceKind {
+    /// Starts a span, which the next matching `End` finishes.
+    Begin,
+    End,
+    /// A single point in time.
+    Instant
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TraceEvent {
+    pub name : &'static str,
+    /// The subsystem which recorded this event, for grouping.
+    pub category : &'static str,
+    pub kind : TraceKind,
+    /// When this happened, from the host's clock.
+    pub time : u64,
+    /// An optional detail, such as the bank switched to.
+    pub arg : Option<(&'static str, u32)>
+}
+
+/// Collects trace events until the host takes them.
+pub struct Tracer {
+    clock : fn() -> u64,
+    events : RefCell<Vec<TraceEvent>>,
+    dropped : Cell<u32>,
+    /// Whether each open span's `Begin` was kept, innermost last.
+    open_spans : RefCell<Vec<bool>>
+}
+
+impl Tracer {
+    fn record(&self, name : &'static str, category : &'static str, kind : TraceKind,
+              arg : Option<(&'static str, u32)>) {
+        let mut events = self.events.borrow_mut();
+
+        // Spans nest, so an end belongs to the innermost open span. Ends without
+        //  a begin are kept, as the host may have started tracing mid-span.
+        let keep = match kind {
+            TraceKind::End => self.open_spans.borrow_mut().pop().unwrap_or(true),
+            TraceKind::Begin => {
+                let keep = events.len() < MAX_EVENTS;
+                self.open_spans.borrow_mut().push(keep);
+                keep
+            },
+            TraceKind::Instant => events.len() < MAX_EVENTS
+        };
+
+        if !keep {
+            self.dropped.set(self.dropped.get().wrapping_add(1));
+            return;
+        }
+
+        events.push(TraceEvent {
+            name,
+            category,
+            kind,
+            time : (self.clock)(),
+            arg
+        });
+    }
+
+    /// Starts a span.
+    pub fn begin(&self, name : &'static str, category : &'static str) {
+        self.record(name, category, TraceKind::Begin, None);
+    }
+
+    /// Finishes the span started by the last matching `begin`.
+    pub fn end(&self, name : &'static str, category : &'static str) {
+        self.record(name, category, TraceKind::End, None);
+    }
+
+    /// Records a single point in time.
+    pub fn instant(&self, name : &'static str, category : &'static str,
+                   arg : Option<(&'static str, u32)>) {
+        self.record(name, category, TraceKind::Instant, arg);
+    }
+
+    /// Takes all events recorded so far. Open spans are tracked separately, so
+    ///  this can be called at any point, not just between spans.
+    pub fn take_events(&self) -> Vec<TraceEvent> {
+        self.events.borrow_mut().drain(..).collect()
+    }
+
+    /// Returns how many events were dropped because they weren't taken in time.
+    pub fn dropped(&self) -> u32 {
+        self.dropped.get()
+    }
+
+    /// Builds a new tracer.
+    ///
+    /// * `clock` - Returns the current time in nanoseconds (from any starting point).
+    pub fn build(clock : fn() -> u64) -> Tracer {
+        Tracer {
+            clock,
+            events : RefCell::new(Vec::new()),
+            dropped : Cell::new(0),
+            open_spans : RefCell::new(Vec::new())
+        }
+    }
+}
@@ -0,0 +1,44 @@
+/**
+ * achievements.rs
+ *
+ * Checks that the RetroAchievements memory map covers its whole address space,
+ *  and reaches every bank of cartridge RAM.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use oxidgb_core::achievements::peek;
+use oxidgb_core::achievements::MEMORY_SIZE;
+use oxidgb_core::achievements::REGIONS;
+
+#[test]
+fn regions_cover_memory() {
+    let mut next = 0;
+
+    for region in REGIONS.iter() {
+        assert_eq!(region.start, next, "gap before {}", region.name);
+        assert!(region.end >= region.start);
+        next = region.end + 1;
+    }
+
+    assert_eq!(next, MEMORY_SIZE);
+}
+
+#[test]
+fn peek_reaches_every_cart_ram_bank() {
+    let mut mem = common::mbc1_memory_with_ram(0x03); // 32KB
+    assert_eq!(mem.rom.cart_ram.len(), 4 * 0x2000);
+
+    for bank in 0 .. 4 {
+        mem.rom.cart_ram[bank * 0x2000 + 0x10] = bank as u8 + 1;
+    }
+
+    // Bank 0 is seen through the CPU's window, and the rest after the GBC's RAM
+    assert_eq!(peek(&mem, 0xA010, 1), 1);
+    assert_eq!(peek(&mem, 0x16010, 1), 2);
+    assert_eq!(peek(&mem, 0x18010, 1), 3);
+    assert_eq!(peek(&mem, 0x1A010, 1), 4);
+    assert_eq!(peek(&mem, MEMORY_SIZE, 1), 0);
+}
@@ -0,0 +1,47 @@
+/**
+ * audit.rs
+ *
+ * Checks that state hashes match between identical runs, and point at the
+ *  component which differs otherwise.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use oxidgb_core::audit::hash_state;
+
+#[test]
+fn identical_runs_match() {
+    let mut first = common::blank_system();
+    let mut second = common::blank_system();
+
+    for _ in 0 .. 10 {
+        first.run(&mut None);
+        second.run(&mut None);
+
+        assert_eq!(hash_state(&first), hash_state(&second));
+    }
+}
+
+#[test]
+fn first_difference_names_component() {
+    let first = common::blank_system();
+    let mut second = common::blank_system();
+
+    second.mem.ram[0x100] ^= 0xFF;
+    assert_eq!(hash_state(&first).first_difference(&hash_state(&second)), Some("Work RAM"));
+
+    second.regs.a ^= 0xFF;
+    assert_eq!(hash_state(&first).first_difference(&hash_state(&second)), Some("CPU"));
+    assert!(hash_state(&first).combined() != hash_state(&second).combined());
+}
+
+#[test]
+fn every_cpu_field_is_hashed() {
+    let first = common::blank_system();
+    let mut second = common::blank_system();
+
+    second.timer_invoke_counter += 1;
+    assert_eq!(hash_state(&first).first_difference(&hash_state(&second)), Some("CPU"));
+}
@@ -0,0 +1,19 @@
+/**
+ * blargg.rs
+ *
+ * Blargg's test ROMs (https://github.com/retrio/gb-test-roms), laid out under
+ *  blargg/ in the test ROM directory as in that repository. Which tests pass is
+ *  tracked in blargg_expected.txt, as the timing and sound tests still fail.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use common::DEFAULT_MAX_FRAMES;
+
+#[test]
+fn blargg_matrix() {
+    common::check_expectations("Blargg", "blargg",
+                               |path| common::run_blargg(path, DEFAULT_MAX_FRAMES));
+}
@@ -0,0 +1,74 @@
+/**
+ * cheats.rs
+ *
+ * Checks cheat code parsing, and that cheats only apply where they should.
+**/
+
+extern crate oxidgb_core;
+
+use oxidgb_core::cheats;
+use oxidgb_core::cheats::CheatEffect;
+use oxidgb_core::cheats::CheatError;
+use oxidgb_core::cheats::CheatManager;
+
+#[test]
+fn game_genie_six_digits() {
+    assert_eq!(cheats::parse("3E1-FFF"), Ok(CheatEffect::RomPatch {
+        address : 0x01FF,
+        value : 0x3E,
+        compare : None
+    }));
+}
+
+#[test]
+fn game_genie_nine_digits() {
+    assert_eq!(cheats::parse("3e1-ffd-6ea"), Ok(CheatEffect::RomPatch {
+        address : 0x21FF,
+        value : 0x3E,
+        compare : Some(0x20)
+    }));
+}
+
+#[test]
+fn game_genie_invalid() {
+    assert_eq!(cheats::parse("3E1-FF"), Err(CheatError::InvalidLength(5)));
+    assert_eq!(cheats::parse("3E1-FGF"), Err(CheatError::InvalidDigit('G')));
+    assert_eq!(cheats::parse("3E1-FF7"), Err(CheatError::InvalidAddress(0x81FF)));
+}
+
+#[test]
+fn game_shark() {
+    assert_eq!(cheats::parse("01FF34C1"), Ok(CheatEffect::RamWrite {
+        address : 0xC134,
+        value : 0xFF
+    }));
+    assert_eq!(cheats::parse("0163FFFF"), Err(CheatError::InvalidAddress(0xFFFF)));
+    assert_eq!(cheats::parse("910134D0"), Err(CheatError::UnsupportedType(0x91)));
+}
+
+#[test]
+fn patches_respect_compare_and_enabled() {
+    let mut manager = CheatManager::default();
+    let index = manager.add("3E1-FFD-6EA", true).unwrap();
+
+    // Wrong address, then wrong compare byte
+    assert_eq!(manager.patch_rom_read(0x2200, 0x20), 0x20);
+    assert_eq!(manager.patch_rom_read(0x21FF, 0x21), 0x21);
+    assert_eq!(manager.patch_rom_read(0x21FF, 0x20), 0x3E);
+
+    assert!(manager.set_enabled(index, false));
+    assert_eq!(manager.patch_rom_read(0x21FF, 0x20), 0x20);
+}
+
+#[test]
+fn ram_writes_only_when_enabled() {
+    let mut manager = CheatManager::default();
+    manager.add("3E1-FFF", true).unwrap();
+    let index = manager.add("01FF34C1", true).unwrap();
+
+    assert_eq!(manager.ram_write(0), None);
+    assert_eq!(manager.ram_write(index), Some((0xC134, 0xFF)));
+
+    manager.set_enabled(index, false);
+    assert_eq!(manager.ram_write(index), None);
+}
@@ -0,0 +1,489 @@
+#![allow(dead_code)]
+
+/**
+ * common/mod.rs
+ *
+ * Runs test ROMs headless, and works out if they passed. Test ROMs aren't
+ *  distributed with the emulator - they are looked for in the directory named by
+ *  OXIDGB_TEST_ROMS, or in core/tests/roms. Tests whose ROMs are missing are skipped.
+ *
+ * Suites which don't fully pass yet are checked against an expected results file
+ *  (<suite>_expected.txt), so that only regressions fail the build.
+ *
+ * Also builds blank systems, for tests which don't need a real game.
+**/
+
+use std::env;
+use std::fs;
+use std::io;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::path::PathBuf;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::cpu::GameboyDebugger;
+use oxidgb_core::mem::GBMemory;
+use oxidgb_core::rom::GameROM;
+
+/// A reasonable upper bound for most test ROMs: a minute of emulated time.
+pub const DEFAULT_MAX_FRAMES : u32 = 60 * 60;
+
+/// Written to $A001 - $A003 by Blargg's tests which report results in cart RAM.
+const BLARGG_SIGNATURE : [u8; 3] = [0xDE, 0xB0, 0x61];
+
+/// Written to $A000 while a Blargg test is still running.
+const BLARGG_RUNNING : u8 = 0x80;
+
+/// Set to rewrite expected results files with the current results.
+const BLESS_VAR : &str = "OXIDGB_BLESS";
+
+/// Mooneye tests finish quickly - this is 20 seconds of emulated time.
+pub const MOONEYE_MAX_FRAMES : u32 = 60 * 20;
+
+/// B, C, D, E, H and L when a Mooneye test passes, and when it fails.
+const MOONEYE_PASS : [u8; 6] = [3, 5, 8, 13, 21, 34];
+const MOONEYE_FAIL : [u8; 6] = [0x42; 6];
+
+/// Mooneye tests execute this (LD B,B) once they have finished.
+const MOONEYE_BREAKPOINT : u8 = 0x40;
+
+/// How a test ROM finished.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    /// The test reported a failure, with the failing sub-test if known.
+    Failed(Option<u32>),
+    /// The test didn't finish within the allowed number of frames.
+    TimedOut,
+    /// The emulator panicked.
+    Crashed(String)
+}
+
+/// The result of running a test ROM.
+pub struct TestRun {
+    pub outcome : Outcome,
+    /// Text the test reported, via serial or cart RAM.
+    pub output : String,
+    pub frames : u32
+}
+
+/// Returns the directory test ROMs are read from, if it exists.
+pub fn roms_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("OXIDGB_TEST_ROMS") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("roms")
+    };
+
+    if dir.is_dir() {
+        Some(dir)
+    } else {
+        None
+    }
+}
+
+/// Finds a test ROM, relative to the test ROM directory. Prints why if it can't
+///  be found, so skipped tests are visible with --nocapture.
+pub fn find_rom(name : &str) -> Option<PathBuf> {
+    let path = match roms_dir() {
+        Some(dir) => dir.join(name),
+        None => {
+            eprintln!("Skipping {}: no test ROM directory (set OXIDGB_TEST_ROMS)", name);
+            return None;
+        }
+    };
+
+    if path.is_file() {
+        Some(path)
+    } else {
+        eprintln!("Skipping {}: {} not found", name, path.display());
+        None
+    }
+}
+
+/// Builds a fresh system around a ROM.
+pub fn load_rom(path : &Path) -> CPU {
+    let data = fs::read(path)
+        .unwrap_or_else(|why| panic!("couldn't read {}: {}", path.display(), why));
+
+    let rom = GameROM::try_build(data)
+        .unwrap_or_else(|why| panic!("couldn't load {}: {:?}", path.display(), why));
+
+    CPU::build(GBMemory::build(rom))
+}
+
+/// A blank 32KB ROM-only cartridge, which runs NOPs forever.
+pub static BLANK_ROM : [u8; 0x8000] = [0; 0x8000];
+
+/// Builds a system around a blank ROM, borrowed rather than copied.
+pub fn blank_system() -> CPU {
+    CPU::build(GBMemory::build(GameROM::try_build_borrowed(&BLANK_ROM).unwrap()))
+}
+
+/// Builds a blank cartridge, sized to match its header.
+///
+/// * `cart_type` - The cartridge type, as stored at $0147.
+/// * `rom_size` - The ROM size code, as stored at $0148.
+/// * `ram_size` - The RAM size code, as stored at $0149.
+pub fn cartridge(cart_type : u8, rom_size : u8, ram_size : u8) -> Vec<u8> {
+    let mut data = vec![0; 0x8000 << rom_size];
+    data[0x147] = cart_type;
+    data[0x148] = rom_size;
+    data[0x149] = ram_size;
+    data
+}
+
+/// Builds memory around cartridge data.
+pub fn memory_from(data : Vec<u8>) -> GBMemory {
+    GBMemory::build(GameROM::build(data))
+}
+
+/// Builds a system around cartridge data.
+pub fn system_from(data : Vec<u8>) -> CPU {
+    CPU::build(memory_from(data))
+}
+
+/// Builds memory for a blank MBC1 cartridge with RAM, given the header's RAM
+///  size code.
+pub fn mbc1_memory_with_ram(ram_size : u8) -> GBMemory {
+    memory_from(cartridge(0x02, 0, ram_size)) // MBC1+RAM
+}
+
+/// Builds memory for a blank MBC1 cartridge with 8KB of RAM.
+pub fn mbc1_ram_memory() -> GBMemory {
+    mbc1_memory_with_ram(0x02)
+}
+
+fn panic_message(payload : Box<::std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Does nothing - for running tests which don't need to watch execution.
+struct NoDebugger;
+
+impl GameboyDebugger for NoDebugger {
+    fn debug(&mut self, _ : &mut CPU) {}
+}
+
+/// Watches for the Mooneye breakpoint, capturing registers when it is hit.
+struct MooneyeWatcher {
+    registers : Option<[u8; 6]>
+}
+
+impl GameboyDebugger for MooneyeWatcher {
+    fn debug(&mut self, cpu : &mut CPU) {
+        if self.registers.is_none() && cpu.mem.read(cpu.regs.pc) == MOONEYE_BREAKPOINT {
+            let regs = &cpu.regs;
+            self.registers = Some([regs.b, regs.c, regs.d, regs.e, regs.h, regs.l]);
+        }
+    }
+}
+
+/// Runs a system frame by frame until `check` reports an outcome, collecting
+///  serial output along the way.
+///
+/// * `check` - Called after each frame with the system and all output so far.
+pub fn run_until<F>(cpu : &mut CPU, max_frames : u32, mut check : F) -> TestRun
+    where F : FnMut(&mut CPU, &str) -> Option<Outcome> {
+    run_with_debugger(cpu, max_frames, &mut NoDebugger, |cpu, _, output| check(cpu, output))
+}
+
+/// Like `run_until`, but with a debugger watching each instruction, which is
+///  also passed to `check`.
+pub fn run_with_debugger<D, F>(cpu : &mut CPU, max_frames : u32, debugger : &mut D,
+                               mut check : F) -> TestRun
+    where D : GameboyDebugger, F : FnMut(&mut CPU, &D, &str) -> Option<Outcome> {
+    let mut output = Vec::new();
+    let mut frames = 0;
+
+    while frames < max_frames {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut hook : Option<&mut GameboyDebugger> = Some(&mut *debugger);
+            cpu.run(&mut hook);
+        }));
+
+        frames += 1;
+        output.extend(cpu.mem.take_serial_output());
+
+        // Nothing is listening, but don't let it pile up
+        cpu.mem.sound.take_samples();
+
+        let text = String::from_utf8_lossy(&output).into_owned();
+
+        if let Err(payload) = result {
+            return TestRun {
+                outcome : Outcome::Crashed(panic_message(payload)),
+                output : text,
+                frames
+            };
+        }
+
+        if let Some(outcome) = check(cpu, debugger, &text) {
+            return TestRun {
+                outcome,
+                output : text,
+                frames
+            };
+        }
+    }
+
+    TestRun {
+        outcome : Outcome::TimedOut,
+        output : String::from_utf8_lossy(&output).into_owned(),
+        frames
+    }
+}
+
+/// Works out which sub-test failed from a Blargg test's output. Single tests
+///  report "Failed #N", while combined ROMs list "NN:ok" for each test, with
+///  an error code in place of "ok" for failures.
+fn blargg_failed_test(output : &str) -> Option<u32> {
+    if let Some(index) = output.find("Failed #") {
+        let number : String = output[index + 8 ..].chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        return number.parse().ok();
+    }
+
+    output.split_whitespace()
+        .filter_map(|word| {
+            let mut parts = word.splitn(2, ':');
+            let test = parts.next()?.parse::<u32>().ok()?;
+            let result = parts.next()?;
+
+            if result != "ok" { Some(test) } else { None }
+        })
+        .next()
+}
+
+/// Checks Blargg's cart RAM protocol, used by tests (such as the sound tests)
+///  which don't report over serial.
+fn blargg_memory_result(cpu : &CPU) -> Option<(Outcome, String)> {
+    let ram = &cpu.mem.rom.cart_ram;
+
+    if ram.len() < 4 || ram[1 .. 4] != BLARGG_SIGNATURE || ram[0] == BLARGG_RUNNING {
+        return None;
+    }
+
+    let text : Vec<u8> = ram[4 ..].iter().cloned().take_while(|x| *x != 0).collect();
+    let text = String::from_utf8_lossy(&text).into_owned();
+
+    let outcome = match ram[0] {
+        0 => Outcome::Passed,
+        code => Outcome::Failed(Some(code as u32))
+    };
+
+    Some((outcome, text))
+}
+
+/// Runs one of Blargg's test ROMs, which report over serial or cart RAM.
+pub fn run_blargg(path : &Path, max_frames : u32) -> TestRun {
+    let mut cpu = load_rom(path);
+    let mut memory_text = None;
+
+    let mut run = run_until(&mut cpu, max_frames, |cpu, output| {
+        if output.contains("Passed") {
+            return Some(Outcome::Passed);
+        }
+
+        if output.contains("Failed") {
+            return Some(Outcome::Failed(blargg_failed_test(output)));
+        }
+
+        let (outcome, text) = blargg_memory_result(cpu)?;
+        memory_text = Some(text);
+        Some(outcome)
+    });
+
+    if let Some(text) = memory_text {
+        run.output.push_str(&text);
+    }
+
+    run
+}
+
+/// Runs one of the Mooneye test ROMs, which signal completion with LD B,B and
+///  report results in registers.
+pub fn run_mooneye(path : &Path, max_frames : u32) -> TestRun {
+    let mut cpu = load_rom(path);
+    let mut watcher = MooneyeWatcher {
+        registers : None
+    };
+
+    run_with_debugger(&mut cpu, max_frames, &mut watcher, |_, watcher, _| {
+        match watcher.registers {
+            Some(MOONEYE_PASS) => Some(Outcome::Passed),
+            Some(MOONEYE_FAIL) => Some(Outcome::Failed(None)),
+            Some(registers) => Some(Outcome::Crashed(
+                format!("finished with unexpected registers {:02X?}", registers))),
+            None => None
+        }
+    })
+}
+
+/// Hashes data using 64-bit FNV-1a.
+pub fn fnv1a(data : &[u8]) -> u64 {
+    let mut hash : u64 = 0xcbf29ce484222325;
+
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+/// Writes a RGB image as a binary PPM.
+pub fn write_ppm(path : &Path, width : usize, height : usize, rgb : &[u8]) -> io::Result<()> {
+    let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    data.extend_from_slice(rgb);
+    fs::write(path, data)
+}
+
+/// Reads a RGB image written by `write_ppm`, if it has the expected size.
+pub fn read_ppm(path : &Path, width : usize, height : usize) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    let header = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+
+    if !data.starts_with(&header) || data.len() != header.len() + width * height * 3 {
+        return None;
+    }
+
+    Some(data[header.len() ..].to_vec())
+}
+
+/// A test ROM, and whether it is expected to pass.
+struct Expectation {
+    path : String,
+    passes : bool
+}
+
+/// Reads an expected results file, keeping its header comments.
+fn read_expectations(path : &Path) -> (String, Vec<Expectation>) {
+    let data = fs::read_to_string(path)
+        .unwrap_or_else(|why| panic!("couldn't read {}: {}", path.display(), why));
+
+    let mut header = String::new();
+    let mut expectations = Vec::new();
+
+    for line in data.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            if expectations.is_empty() {
+                header.push_str(line);
+                header.push('\n');
+            }
+            continue;
+        }
+
+        let mut parts = line.splitn(2, ' ');
+        let passes = match parts.next() {
+            Some("pass") => true,
+            Some("fail") => false,
+            _ => panic!("Invalid expectation: {:?}", line)
+        };
+        let path = parts.next().expect("Expectation is missing a path").trim();
+
+        expectations.push(Expectation {
+            path : path.to_string(),
+            passes
+        });
+    }
+
+    (header, expectations)
+}
+
+fn describe(outcome : &Outcome) -> String {
+    match *outcome {
+        Outcome::Passed => "pass".to_string(),
+        Outcome::Failed(Some(test)) => format!("FAIL (#{})", test),
+        Outcome::Failed(None) => "FAIL".to_string(),
+        Outcome::TimedOut => "TIMEOUT".to_string(),
+        Outcome::Crashed(ref why) => format!("CRASH ({})", why)
+    }
+}
+
+/// Runs every test ROM listed in a suite's expected results file, failing if
+///  any test expected to pass doesn't. With OXIDGB_BLESS set, the file is
+///  rewritten with the current results instead.
+///
+/// * `suite` - The suite's name, for messages.
+/// * `dir` - Where the suite's ROMs are in the test ROM directory. Its results
+///  are tracked in tests/<dir>_expected.txt.
+/// * `run` - Runs a single test ROM.
+pub fn check_expectations<F>(suite : &str, dir : &str, mut run : F)
+    where F : FnMut(&Path) -> TestRun {
+    if roms_dir().is_none() {
+        eprintln!("Skipping {} tests: no test ROM directory (set OXIDGB_TEST_ROMS)", suite);
+        return;
+    }
+
+    let expectations_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+        .join(format!("{}_expected.txt", dir));
+
+    let bless = env::var_os(BLESS_VAR).is_some();
+    let (header, mut expectations) = read_expectations(&expectations_path);
+
+    let mut regressions = Vec::new();
+    let mut improvements = Vec::new();
+    let mut passed = 0;
+    let mut ran = 0;
+
+    for expectation in &mut expectations {
+        let path = match find_rom(&format!("{}/{}", dir, expectation.path)) {
+            Some(path) => path,
+            None => continue
+        };
+
+        let test_run = run(&path);
+        let passes = test_run.outcome == Outcome::Passed;
+
+        println!("{:<8} {}", describe(&test_run.outcome), expectation.path);
+
+        ran += 1;
+        if passes {
+            passed += 1;
+        }
+
+        if passes != expectation.passes {
+            if passes {
+                improvements.push(expectation.path.clone());
+            } else {
+                println!("{}", test_run.output);
+                regressions.push(expectation.path.clone());
+            }
+
+            if bless {
+                expectation.passes = passes;
+            }
+        }
+    }
+
+    println!("{} of {} {} tests passed", passed, ran, suite);
+
+    if bless {
+        let mut data = header;
+        for expectation in &expectations {
+            data.push_str(&format!("{} {}\n", if expectation.passes { "pass" } else { "fail" },
+                                   expectation.path));
+        }
+
+        fs::write(&expectations_path, data)
+            .unwrap_or_else(|why| panic!("couldn't write {}: {}", expectations_path.display(), why));
+        println!("Updated {}", expectations_path.display());
+        return;
+    }
+
+    if !improvements.is_empty() {
+        println!("Now passing (run with {}=1 to record): {:?}", BLESS_VAR, improvements);
+    }
+
+    if !regressions.is_empty() {
+        panic!("{} {} test(s) no longer pass: {:?}", regressions.len(), suite, regressions);
+    }
+}
@@ -0,0 +1,45 @@
+/**
+ * diff.rs
+ *
+ * Checks that state diffs report each differing register and memory range.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use oxidgb_core::debug::diff::diff_states;
+use oxidgb_core::debug::diff::Difference;
+
+#[test]
+fn identical_states_have_no_differences() {
+    assert_eq!(diff_states(&common::blank_system(), &common::blank_system()), vec![]);
+}
+
+#[test]
+fn reports_registers_and_memory() {
+    let left = common::blank_system();
+    let mut right = common::blank_system();
+
+    right.regs.pc = 0x1234;
+    right.mem.ram[0x10] = 0xAA;
+    right.mem.ram[0x20] = 0xBB;
+
+    let differences = diff_states(&left, &right);
+
+    assert_eq!(differences, vec![
+        Difference::Register { name : "PC", left : left.regs.pc as u32, right : 0x1234 },
+        Difference::Memory { name : "Work RAM", offset : 0x10, count : 2,
+                             left : left.mem.ram[0x10], right : 0xAA }
+    ]);
+}
+
+#[test]
+fn reports_components_without_compared_fields() {
+    let left = common::blank_system();
+    let mut right = common::blank_system();
+
+    right.mem.gpu.sprite_limit = !left.mem.gpu.sprite_limit;
+
+    assert_eq!(diff_states(&left, &right), vec![Difference::Opaque { name : "Video" }]);
+}
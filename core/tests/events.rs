@@ -0,0 +1,90 @@
+/**
+ * events.rs
+ *
+ * Checks that the core reports events for the frontend, and that one-off
+ *  events are only reported once.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use oxidgb_core::events::CoreEvent;
+use oxidgb_core::events::EventQueue;
+
+#[test]
+fn save_ram_written_once_per_frame() {
+    let mut mem = common::mbc1_ram_memory();
+
+    mem.write(0xA000, 1);
+    mem.write(0xA001, 2);
+    assert_eq!(mem.events.take_events(), vec![CoreEvent::SaveRamWritten]);
+
+    mem.end_frame();
+    mem.write(0xA000, 3);
+    assert_eq!(mem.events.take_events(), vec![CoreEvent::SaveRamWritten]);
+}
+
+#[test]
+fn unsupported_features_reported_once() {
+    let mut mem = common::mbc1_ram_memory();
+
+    mem.write(0xFF4D, 1);
+    mem.write(0xFF4D, 1);
+    mem.read(0xFF4D);
+
+    assert_eq!(mem.events.take_events(),
+               vec![CoreEvent::UnsupportedFeature(String::from("I/O register FF4D"))]);
+}
+
+#[test]
+fn mbc2_ignores_banking_writes() {
+    let mut mem = common::memory_from(common::cartridge(0x05, 0, 0)); // MBC2
+
+    // MBC2 has neither RAM banking nor memory models to report
+    mem.write(0x4000, 1);
+    mem.write(0x6000, 1);
+
+    assert_eq!(mem.events.take_events(), vec![]);
+}
+
+#[test]
+fn serial_reports_link_and_printer() {
+    let mut mem = common::mbc1_ram_memory();
+
+    for byte in &[0x88, 0x33, 0x02] {
+        mem.write(0xFF01, *byte);
+        mem.write(0xFF02, 0x81);
+    }
+
+    assert_eq!(mem.events.take_events(),
+               vec![CoreEvent::LinkConnected, CoreEvent::PrinterJobReady]);
+}
+
+#[test]
+fn serial_output_keeps_the_latest_bytes() {
+    let mut mem = common::mbc1_ram_memory();
+
+    for i in 0 .. 5000 {
+        mem.write(0xFF01, i as u8);
+        mem.write(0xFF02, 0x81);
+    }
+
+    let output = mem.take_serial_output();
+    assert_eq!(output.len(), 4096);
+    assert_eq!(output[0], (5000 - 4096) as u8);
+    assert_eq!(output[4095], (4999 % 256) as u8);
+    assert!(mem.take_serial_output().is_empty());
+}
+
+#[test]
+fn queue_is_bounded() {
+    let queue = EventQueue::default();
+
+    for _ in 0 .. 100 {
+        queue.push(CoreEvent::SaveRamWritten);
+    }
+
+    assert_eq!(queue.take_events().len(), 64);
+    assert_eq!(queue.dropped(), 36);
+}
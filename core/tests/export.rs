@@ -0,0 +1,76 @@
+/**
+ * export.rs
+ *
+ * Checks coverage recording, and whole-bank disassembly guided by it.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use oxidgb_core::debug::coverage::CodeCoverage;
+use oxidgb_core::debug::export::bank_count;
+use oxidgb_core::debug::export::export_bank;
+
+/// A ROM which jumps from the entry point to a loop at $0150.
+fn build_rom() -> Vec<u8> {
+    let mut data = common::cartridge(0x00, 0, 0);
+    data[0x100 .. 0x103].copy_from_slice(&[0xC3, 0x50, 0x01]); // jp $0150
+    data[0x150 .. 0x152].copy_from_slice(&[0x18, 0xFE]); // jr $0150
+    data
+}
+
+#[test]
+fn records_executed_instructions() {
+    let data = build_rom();
+    let mut cpu = common::system_from(data.clone());
+    cpu.mem.coverage = Some(CodeCoverage::build(data.len()));
+
+    cpu.run(&mut None);
+
+    let coverage = cpu.mem.coverage.as_ref().unwrap();
+    assert!(coverage.is_code(0x100));
+    assert!(coverage.is_code(0x150));
+    assert!(!coverage.is_code(0x101));
+    assert!(!coverage.is_code(0x151));
+    assert_eq!(coverage.count(), 2);
+
+    // Saved maps load back the same
+    let reloaded = CodeCoverage::from_bytes(coverage.as_bytes().to_vec(), data.len());
+    assert_eq!(reloaded.count(), 2);
+}
+
+#[test]
+fn coverage_separates_code_from_data() {
+    let data = build_rom();
+
+    let mut coverage = CodeCoverage::build(data.len());
+    coverage.record(0x100, 1);
+    coverage.record(0x150, 1);
+
+    assert_eq!(bank_count(&data), 2);
+
+    let listing = export_bank(&data, 0, Some(&coverage));
+    assert!(listing.starts_with("SECTION \"ROM Bank $00\", ROM0[$0000]"));
+    assert!(listing.contains("    jp $0150"));
+    assert!(listing.contains("\nL00_0150:\n    jr $0150"));
+    assert!(listing.contains("    db $00, $00, $00, $00, $00, $00, $00, $00"));
+    assert!(!listing.contains("nop"));
+
+    // Without coverage, everything is treated as code
+    let listing = export_bank(&data, 1, None);
+    assert!(listing.starts_with("SECTION \"ROM Bank $01\", ROMX[$4000], BANK[$01]"));
+    assert!(listing.contains("    nop"));
+    assert!(!listing.contains("db"));
+}
+
+#[test]
+fn mid_instruction_targets_are_not_labelled() {
+    let mut data = common::cartridge(0x00, 0, 0);
+    data[0x100 .. 0x103].copy_from_slice(&[0xC3, 0x51, 0x01]); // jp $0151
+    data[0x150 .. 0x152].copy_from_slice(&[0x3E, 0x00]); // ld a,$00
+
+    let listing = export_bank(&data, 0, None);
+    assert!(listing.contains("    jp $0151"));
+    assert!(!listing.contains("L00_0151"));
+}
@@ -0,0 +1,244 @@
+/**
+ * golden.rs
+ *
+ * Runs ROMs for a fixed number of frames, and compares the final screen against
+ *  the frames recorded in golden/frames.txt. On a mismatch, the actual frame and
+ *  a diff against the expected frame are written to target/golden.
+ *
+ * Run with `cargo test --test golden -- --bless` (or with OXIDGB_BLESS set) to
+ *  record the current frames as expected.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+
+const WIDTH : usize = 160;
+const HEIGHT : usize = 144;
+
+/// Set to record the current frames, as an alternative to --bless.
+const BLESS_VAR : &str = "OXIDGB_BLESS";
+
+struct Golden {
+    frames : u32,
+    hash : Option<u64>,
+    path : String
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn frames_path() -> PathBuf {
+    golden_dir().join("frames.txt")
+}
+
+/// Where frames which don't match are written.
+fn output_dir() -> PathBuf {
+    match env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("target")
+    }.join("golden")
+}
+
+/// Turns a ROM path into a file name for its images.
+fn image_name(path : &str, suffix : &str) -> String {
+    let stem : String = path.trim_end_matches(".gb")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+
+    format!("{}{}.ppm", stem, suffix)
+}
+
+/// Reads the expectations file, keeping its header comments.
+fn read_goldens() -> (String, Vec<Golden>) {
+    let data = fs::read_to_string(frames_path())
+        .expect("couldn't read golden/frames.txt");
+
+    let mut header = String::new();
+    let mut goldens = Vec::new();
+
+    for line in data.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            if goldens.is_empty() {
+                header.push_str(line);
+                header.push('\n');
+            }
+            continue;
+        }
+
+        let mut parts = line.splitn(3, ' ');
+        let frames = parts.next().and_then(|x| x.parse().ok())
+            .unwrap_or_else(|| panic!("Invalid frame count: {:?}", line));
+        let hash = match parts.next() {
+            Some("-") => None,
+            Some(hash) => Some(u64::from_str_radix(hash, 16)
+                .unwrap_or_else(|_| panic!("Invalid hash: {:?}", line))),
+            None => panic!("Golden frame is missing a hash: {:?}", line)
+        };
+        let path = parts.next().expect("Golden frame is missing a path").trim();
+
+        goldens.push(Golden {
+            frames,
+            hash,
+            path : path.to_string()
+        });
+    }
+
+    (header, goldens)
+}
+
+fn write_goldens(header : String, goldens : &[Golden]) {
+    let mut data = header;
+
+    for golden in goldens {
+        let hash = match golden.hash {
+            Some(hash) => format!("{:016x}", hash),
+            None => "-".to_string()
+        };
+
+        data.push_str(&format!("{} {} {}\n", golden.frames, hash, golden.path));
+    }
+
+    fs::write(frames_path(), data).expect("couldn't write golden/frames.txt");
+}
+
+/// Runs a ROM for a number of frames, returning the final screen.
+fn render(path : &Path, frames : u32) -> Result<Vec<u8>, String> {
+    let mut cpu = common::load_rom(path);
+
+    let run = common::run_until(&mut cpu, frames, |_, _| None);
+
+    match run.outcome {
+        common::Outcome::Crashed(why) => Err(format!("crashed after {} frames: {}", run.frames, why)),
+        _ => Ok(cpu.mem.gpu.pixel_data.clone())
+    }
+}
+
+/// Highlights differing pixels in red over a faded copy of the expected frame.
+fn diff_image(expected : &[u8], actual : &[u8]) -> Vec<u8> {
+    let mut diff = Vec::with_capacity(expected.len());
+
+    for (expected, actual) in expected.chunks(3).zip(actual.chunks(3)) {
+        if expected == actual {
+            let luma = (expected[0] as u32 + expected[1] as u32 + expected[2] as u32) / 3;
+            let faded = (luma / 4 + 192) as u8;
+            diff.extend_from_slice(&[faded, faded, faded]);
+        } else {
+            diff.extend_from_slice(&[0xFF, 0x00, 0x00]);
+        }
+    }
+
+    diff
+}
+
+/// Writes the actual frame, and a diff if the expected frame is available.
+fn dump_mismatch(golden : &Golden, actual : &[u8]) {
+    let dir = output_dir();
+    if let Err(why) = fs::create_dir_all(&dir) {
+        eprintln!("  couldn't create {}: {}", dir.display(), why);
+        return;
+    }
+
+    let actual_path = dir.join(image_name(&golden.path, ".actual"));
+    match common::write_ppm(&actual_path, WIDTH, HEIGHT, actual) {
+        Ok(_) => println!("  actual frame: {}", actual_path.display()),
+        Err(why) => eprintln!("  couldn't write {}: {}", actual_path.display(), why)
+    }
+
+    let expected_path = golden_dir().join(image_name(&golden.path, ""));
+    let expected = match common::read_ppm(&expected_path, WIDTH, HEIGHT) {
+        Some(expected) => expected,
+        None => {
+            println!("  no expected image at {}", expected_path.display());
+            return;
+        }
+    };
+
+    let differing = expected.chunks(3).zip(actual.chunks(3)).filter(|&(a, b)| a != b).count();
+    let diff_path = dir.join(image_name(&golden.path, ".diff"));
+    match common::write_ppm(&diff_path, WIDTH, HEIGHT, &diff_image(&expected, actual)) {
+        Ok(_) => println!("  {} pixels differ: {}", differing, diff_path.display()),
+        Err(why) => eprintln!("  couldn't write {}: {}", diff_path.display(), why)
+    }
+}
+
+fn main() {
+    let bless = env::args().any(|arg| arg == "--bless") || env::var_os(BLESS_VAR).is_some();
+
+    if common::roms_dir().is_none() {
+        eprintln!("Skipping golden frame tests: no test ROM directory (set OXIDGB_TEST_ROMS)");
+        return;
+    }
+
+    let (header, mut goldens) = read_goldens();
+
+    let mut failures = Vec::new();
+    let mut passed = 0;
+
+    for golden in &mut goldens {
+        let path = match common::find_rom(&golden.path) {
+            Some(path) => path,
+            None => continue
+        };
+
+        let frame = match render(&path, golden.frames) {
+            Ok(frame) => frame,
+            Err(why) => {
+                println!("CRASH    {}: {}", golden.path, why);
+                failures.push(golden.path.clone());
+                continue;
+            }
+        };
+
+        let hash = common::fnv1a(&frame);
+
+        if bless {
+            if golden.hash != Some(hash) {
+                println!("update   {} ({:016x})", golden.path, hash);
+            }
+
+            golden.hash = Some(hash);
+
+            let image_path = golden_dir().join(image_name(&golden.path, ""));
+            common::write_ppm(&image_path, WIDTH, HEIGHT, &frame)
+                .unwrap_or_else(|why| panic!("couldn't write {}: {}", image_path.display(), why));
+            continue;
+        }
+
+        match golden.hash {
+            Some(expected) if expected == hash => {
+                println!("ok       {}", golden.path);
+                passed += 1;
+            },
+            Some(expected) => {
+                println!("MISMATCH {} (expected {:016x}, got {:016x})", golden.path, expected, hash);
+                dump_mismatch(golden, &frame);
+                failures.push(golden.path.clone());
+            },
+            None => {
+                println!("UNKNOWN  {} ({:016x}) - run with --bless to record", golden.path, hash);
+                failures.push(golden.path.clone());
+            }
+        }
+    }
+
+    if bless {
+        write_goldens(header, &goldens);
+        println!("Updated {}", frames_path().display());
+        return;
+    }
+
+    println!("{} golden frame(s) matched, {} failed", passed, failures.len());
+
+    if !failures.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,78 @@
+/**
+ * hooks.rs
+ *
+ * Checks that hooks run for the right events, and can change memory.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::hooks::HookEvent;
+
+/// Builds a system which writes $42 to $C000, then loops forever.
+fn build_system() -> CPU {
+    let mut data = common::cartridge(0x00, 0, 0);
+    data[0x100 .. 0x107].copy_from_slice(&[
+        0x3E, 0x42,       // LD A,$42
+        0xEA, 0x00, 0xC0, // LD ($C000),A
+        0x18, 0xFE        // JR -2
+    ]);
+
+    common::system_from(data)
+}
+
+#[test]
+fn hooks_see_accesses_and_frames() {
+    let mut cpu = build_system();
+
+    let writes = Rc::new(Cell::new(0));
+    let loops = Rc::new(Cell::new(0));
+    let frames = Rc::new(Cell::new(0));
+
+    let counter = writes.clone();
+    cpu.mem.hooks.on_write(0xC000, Box::new(move |mem, event| {
+        assert_eq!(event, HookEvent::Write { address : 0xC000, value : 0x42 });
+        counter.set(counter.get() + 1);
+
+        // Hooks can change memory, without triggering other hooks
+        mem.write(0xC001, 0x99);
+    }));
+
+    let counter = loops.clone();
+    cpu.mem.hooks.on_execute(0x0105, Box::new(move |_, _| counter.set(counter.get() + 1)));
+
+    let counter = frames.clone();
+    cpu.mem.hooks.on_frame(Box::new(move |_, _| counter.set(counter.get() + 1)));
+
+    cpu.mem.hooks.on_read(0xC000, Box::new(|_, _| panic!("$C000 is never read")));
+
+    for _ in 0 .. 2 {
+        cpu.run(&mut None);
+    }
+
+    assert_eq!(writes.get(), 1);
+    assert!(loops.get() > 1000);
+    assert_eq!(frames.get(), 2);
+    assert_eq!(cpu.mem.ram[1], 0x99);
+}
+
+#[test]
+fn removed_hooks_stop_running() {
+    let mut cpu = build_system();
+
+    let frames = Rc::new(Cell::new(0));
+    let counter = frames.clone();
+    let id = cpu.mem.hooks.on_frame(Box::new(move |_, _| counter.set(counter.get() + 1)));
+
+    cpu.run(&mut None);
+    assert!(cpu.mem.hooks.remove(id));
+    cpu.run(&mut None);
+
+    assert_eq!(frames.get(), 1);
+    assert!(!cpu.mem.hooks.remove(id));
+}
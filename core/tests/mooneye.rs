@@ -0,0 +1,19 @@
+/**
+ * mooneye.rs
+ *
+ * Runs the Mooneye test suite, tracking which tests pass in mooneye_expected.txt.
+ *  Only regressions fail the build, so the suite can run while many timing tests
+ *  still fail.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use common::MOONEYE_MAX_FRAMES;
+
+#[test]
+fn mooneye_matrix() {
+    common::check_expectations("Mooneye", "mooneye",
+                               |path| common::run_mooneye(path, MOONEYE_MAX_FRAMES));
+}
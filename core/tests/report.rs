@@ -0,0 +1,78 @@
+/**
+ * report.rs
+ *
+ * Checks memory reports, that borrowed ROMs aren't copied, and that ROMs are
+ *  kept out of save states.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::mem::GBMemory;
+use oxidgb_core::report::memory_report;
+use oxidgb_core::rom::GameROM;
+
+#[test]
+fn borrowed_rom_is_not_copied() {
+    let cpu = common::blank_system();
+    assert!(cpu.mem.rom.is_borrowed());
+
+    let report = memory_report(&cpu);
+
+    assert_eq!(report.rom_copy, 0);
+    assert_eq!(report.framebuffer, 160 * 144 * 3);
+    assert_eq!(report.work_ram, 8192 + 127);
+}
+
+#[test]
+fn copied_rom_is_counted() {
+    let rom = GameROM::try_build(common::BLANK_ROM.to_vec()).unwrap();
+    assert!(!rom.is_borrowed());
+
+    let cpu = CPU::build(GBMemory::build(rom));
+    let report = memory_report(&cpu);
+
+    assert_eq!(report.rom_copy, common::BLANK_ROM.len());
+    assert!(report.total() > common::BLANK_ROM.len() + report.framebuffer);
+}
+
+#[test]
+fn save_states_exclude_the_rom() {
+    let large = common::system_from(common::cartridge(0x01, 0x01, 0)); // 64KB MBC1
+
+    assert_eq!(memory_report(&large).savestate,
+               memory_report(&common::blank_system()).savestate);
+}
+
+#[test]
+fn rom_data_can_be_attached_to_a_loaded_state() {
+    let mut running = common::blank_system();
+
+    // As deserialized from a save state
+    let mut loaded = common::blank_system();
+    loaded.mem.rom.take_data();
+    assert!(loaded.mem.rom.data().is_empty());
+
+    loaded.mem.rom.attach_data(running.mem.rom.take_data());
+
+    assert_eq!(loaded.mem.rom.data().len(), common::BLANK_ROM.len());
+    assert!(loaded.mem.rom.is_borrowed());
+}
+
+#[test]
+fn header_is_kept_without_rom_data() {
+    let mut data = common::cartridge(0x00, 0, 0);
+    data[0x143] = 0x80; // CGB compatible
+    data[0x14D] = 0x12;
+    data[0x14E] = 0x34;
+    data[0x14F] = 0x56;
+
+    let mut cpu = common::system_from(data);
+    cpu.mem.rom.take_data();
+
+    assert_eq!(cpu.mem.rom.header_checksum(), 0x12);
+    assert_eq!(cpu.mem.rom.global_checksum(), 0x3456);
+    assert!(cpu.mem.rom.supports_cgb());
+}
@@ -0,0 +1,61 @@
+/**
+ * scan.rs
+ *
+ * Checks that RAM searches narrow down to the right addresses.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use oxidgb_core::debug::scan::Comparison;
+use oxidgb_core::debug::scan::MemoryScanner;
+use oxidgb_core::hooks;
+
+#[test]
+fn narrows_down_changing_values() {
+    let mut mem = common::mbc1_ram_memory();
+    let mut scanner = MemoryScanner::build();
+
+    mem.write(0xC100, 10);
+    mem.write(0xA010, 10);
+    mem.write(0xFF90, 10);
+    scanner.filter(&mem, Comparison::Exact(10));
+    assert_eq!(scanner.candidates().len(), 3);
+
+    mem.write(0xC100, 9);
+    mem.write(0xA010, 11);
+    scanner.filter(&mem, Comparison::Changed);
+    assert_eq!(scanner.candidates().len(), 2);
+
+    scanner.filter(&mem, Comparison::Unchanged);
+    assert_eq!(scanner.candidates().len(), 2);
+
+    mem.write(0xC100, 8);
+    mem.write(0xA010, 12);
+    scanner.filter(&mem, Comparison::Decreased);
+
+    let candidates = scanner.candidates();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].address, 0xC100);
+    assert_eq!(candidates[0].previous, 8);
+}
+
+#[test]
+fn scanning_does_not_trigger_read_hooks() {
+    let mut mem = common::mbc1_ram_memory();
+    let mut scanner = MemoryScanner::build();
+
+    let reads = Rc::new(Cell::new(0));
+    let counter = reads.clone();
+    mem.hooks.on_read(0xC100, Box::new(move |_, _| counter.set(counter.get() + 1)));
+
+    scanner.start(&mem);
+    scanner.filter(&mem, Comparison::Unchanged);
+    hooks::dispatch_pending(&mut mem);
+
+    assert_eq!(reads.get(), 0);
+}
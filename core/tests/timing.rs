@@ -0,0 +1,55 @@
+#![cfg(feature = "profiling")]
+
+/**
+ * timing.rs
+ *
+ * Checks per-subsystem timing. Run with `--features profiling`.
+**/
+
+extern crate oxidgb_core;
+
+mod common;
+
+use std::cell::Cell;
+
+use oxidgb_core::debug::timing::reset_profiling;
+use oxidgb_core::debug::timing::start_profiling;
+use oxidgb_core::debug::timing::subsystem_timings;
+use oxidgb_core::profiler::Subsystem;
+
+thread_local!(static TICKS : Cell<u64> = Cell::new(0));
+
+/// A clock which advances on every reading, so every subsystem is charged.
+fn ticking_clock() -> u64 {
+    TICKS.with(|ticks| {
+        ticks.set(ticks.get() + 1);
+        ticks.get()
+    })
+}
+
+#[test]
+fn counts_work_per_subsystem() {
+    let mut cpu = common::blank_system();
+    assert!(subsystem_timings(&cpu).is_empty());
+
+    start_profiling(&mut cpu, ticking_clock);
+    cpu.run(&mut None);
+
+    let timings = subsystem_timings(&cpu);
+    assert!(timings.iter().all(|x| x.time > 0 || x.subsystem == Subsystem::Mmu));
+
+    reset_profiling(&cpu);
+    assert!(subsystem_timings(&cpu).iter().all(|x| x.time == 0 && x.count == 0));
+
+    // A whole frame, from one VBlank to the next, with room for its samples
+    cpu.mem.sound.take_samples();
+    cpu.run(&mut None);
+
+    let timings = subsystem_timings(&cpu);
+    let count = |subsystem| timings.iter().find(|x| x.subsystem == subsystem).unwrap().count;
+
+    // 154 lines, plus one from VBlank currently running a line long
+    assert!(count(Subsystem::Cpu) > 0);
+    assert_eq!(count(Subsystem::Ppu), 155);
+    assert!(count(Subsystem::Apu) > 0 && count(Subsystem::Apu) < count(Subsystem::Cpu));
+}
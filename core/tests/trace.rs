@@ -0,0 +1,64 @@
+/**
+ * trace.rs
+ *
+ * Checks that traces stay balanced when events have to be dropped.
+**/
+
+extern crate oxidgb_core;
+
+use oxidgb_core::trace::TraceKind;
+use oxidgb_core::trace::Tracer;
+
+fn clock() -> u64 {
+    0
+}
+
+#[test]
+fn spans_are_dropped_whole() {
+    let tracer = Tracer::build(clock);
+
+    // Fill the tracer up inside a span
+    tracer.begin("Frame", "frame");
+    for _ in 0 .. 1 << 16 {
+        tracer.instant("Tick", "test", None);
+    }
+
+    tracer.begin("Inner", "test");
+    tracer.end("Inner", "test");
+    tracer.end("Frame", "frame");
+
+    let events = tracer.take_events();
+    let count = |kind| events.iter().filter(|x| x.kind == kind).count();
+
+    assert_eq!(count(TraceKind::Begin), 1);
+    assert_eq!(count(TraceKind::End), 1);
+    assert_eq!(events.last().map(|x| (x.name, x.kind)), Some(("Frame", TraceKind::End)));
+
+    // The last tick, and the inner span
+    assert_eq!(tracer.dropped(), 3);
+}
+
+#[test]
+fn draining_keeps_spans_paired() {
+    let tracer = Tracer::build(clock);
+
+    tracer.begin("Frame", "frame");
+    for _ in 0 .. (1 << 16) - 1 {
+        tracer.instant("Tick", "test", None);
+    }
+
+    // Full, so this span is dropped - then the host makes room while it's open
+    tracer.begin("Outer", "test");
+    tracer.take_events();
+
+    tracer.begin("Inner", "test");
+    tracer.end("Inner", "test");
+    tracer.end("Outer", "test");
+    tracer.end("Frame", "frame");
+
+    let events : Vec<_> = tracer.take_events().iter().map(|x| (x.name, x.kind)).collect();
+
+    assert_eq!(events, vec![("Inner", TraceKind::Begin), ("Inner", TraceKind::End),
+                            ("Frame", TraceKind::End)]);
+    assert_eq!(tracer.dropped(), 2);
+}
@@ -0,0 +1,199 @@
+/**
+ * audio.rs
+ *
+ * Feeds samples from the emulator into the host's audio device.
+**/
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use rodio;
+use rodio::Sink;
+use rodio::Source;
+use rodio::queue::queue;
+use rodio::queue::SourcesQueueInput;
+use rodio::buffer::SamplesBuffer;
+
+use oxidgb_core::sound::OUTPUT_FREQUENCY;
+
+/// The core generates samples at full scale, which is far too loud.
+const VOLUME_SCALE : f32 = 1f32 / 100f32;
+
+/// How much audio is kept queued by default.
+pub const DEFAULT_LATENCY_MS : u64 = 50;
+
+/// Audio is submitted a frame at a time, so anything lower than this will underrun.
+pub const MIN_LATENCY_MS : u64 = 20;
+
+/// Returns the names of all available output devices.
+pub fn device_names() -> Vec<String> {
+    rodio::output_devices().map(|device| device.name()).collect()
+}
+
+/// Wraps a source, counting each sample as the device pulls it.
+struct PlaybackCounter<S> {
+    inner : S,
+    played : Arc<AtomicUsize>
+}
+
+impl<S> Iterator for PlaybackCounter<S> where S : Source<Item = f32> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next();
+
+        if sample.is_some() {
+            self.played.fetch_add(1, Ordering::Relaxed);
+        }
+
+        sample
+    }
+}
+
+impl<S> Source for PlaybackCounter<S> where S : Source<Item = f32> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+pub struct AudioOutput {
+    sink : Sink,
+    input : Arc<SourcesQueueInput<f32>>,
+
+    volume : f32,
+
+    /// Samples handed to the device, and samples it has actually played.
+    submitted : usize,
+    played : Arc<AtomicUsize>,
+
+    /// Queued audio measured after each submission, for reporting latency.
+    measured_total : Duration,
+    measured_count : u32
+}
+
+impl AudioOutput {
+    /// Queues a frame of interleaved stereo samples for playback.
+    ///
+    /// * `speed` - The speed the emulator is running at. Samples are averaged down
+    ///             to keep up, or dropped entirely if running uncapped (None).
+    pub fn submit(&mut self, samples : &[f32], speed : Option<u32>) {
+        let speed = match speed {
+            Some(value) => value as usize,
+            None => return
+        };
+
+        let mut output = Vec::with_capacity(samples.len() / speed + 2);
+
+        for frame in samples.chunks(2 * speed) {
+            let mut left = 0f32;
+            let mut right = 0f32;
+            let count = (frame.len() / 2) as f32;
+
+            if count == 0f32 {
+                continue;
+            }
+
+            for pair in frame.chunks(2) {
+                left += pair[0];
+                if pair.len() > 1 {
+                    right += pair[1];
+                }
+            }
+
+            output.push(left / count * VOLUME_SCALE * self.volume);
+            output.push(right / count * VOLUME_SCALE * self.volume);
+        }
+
+        self.submitted += output.len();
+
+        let buffer = SamplesBuffer::new(2, OUTPUT_FREQUENCY, output);
+        self.input.append(PlaybackCounter {
+            inner : buffer,
+            played : self.played.clone()
+        });
+
+        self.measured_total += self.queued();
+        self.measured_count += 1;
+    }
+
+    /// Returns the average amount of audio queued since this was last called, if
+    ///  any audio has been submitted. This doesn't include the device's own buffer,
+    ///  which isn't exposed to us.
+    pub fn take_average_latency(&mut self) -> Option<Duration> {
+        if self.measured_count == 0 {
+            return None;
+        }
+
+        let average = self.measured_total / self.measured_count;
+        self.measured_total = Duration::from_millis(0);
+        self.measured_count = 0;
+
+        Some(average)
+    }
+
+    /// Returns how much audio is waiting to be played.
+    pub fn queued(&self) -> Duration {
+        let played = self.played.load(Ordering::Relaxed);
+        let pending = self.submitted.saturating_sub(played) as u64;
+
+        // Interleaved stereo
+        let micros = pending * 1_000_000 / (OUTPUT_FREQUENCY as u64 * 2);
+        Duration::from_micros(micros)
+    }
+
+    /// Opens an audio device.
+    ///
+    /// * `device_name` - The name of the device to use. If missing or not found,
+    ///                   the default device is used.
+    /// * `volume` - Output volume, from 0 to 1.
+    pub fn build(device_name : Option<&str>, volume : f32) -> AudioOutput {
+        let named_device = device_name.and_then(|name| {
+            let device = rodio::output_devices()
+                .find(|device| device.name().eq_ignore_ascii_case(name));
+
+            if device.is_none() {
+                warn!("No audio device named {:?} - using the default. Available devices: {:?}",
+                      name, device_names());
+            }
+
+            device
+        });
+
+        let device = match named_device {
+            Some(device) => device,
+            None => rodio::default_output_device().unwrap()
+        };
+
+        info!("Audio device: {}", device.name());
+
+        let sink = Sink::new(&device);
+        let (input, output) = queue(true);
+        sink.append(output);
+        sink.play();
+
+        return AudioOutput {
+            sink,
+            input,
+            volume : volume.max(0f32).min(1f32),
+            submitted : 0,
+            played : Arc::new(AtomicUsize::new(0)),
+
+            measured_total : Duration::from_millis(0),
+            measured_count : 0
+        }
+    }
+}
@@ -0,0 +1,105 @@
+/**
+ * bench.rs
+ *
+ * Runs a game headless as fast as possible, reporting how quickly it ran and
+ *  (with the "profiling" feature) where the time went.
+**/
+
+use std::path::Path;
+use std::time::Instant;
+
+use oxidgb_core::cpu::CPU;
+#[cfg(feature = "profiling")]
+use oxidgb_core::debug::timing::start_profiling;
+#[cfg(feature = "profiling")]
+use oxidgb_core::debug::timing::subsystem_timings;
+use oxidgb_core::report::memory_report;
+
+#[cfg(feature = "profiling")]
+use clock;
+use clock::nanos;
+use limiter::GAMEBOY_FRAMERATE;
+use loader;
+
+fn seconds(nanos : u64) -> f64 {
+    nanos as f64 / 1_000_000_000.0
+}
+
+fn kilobytes(bytes : usize) -> f64 {
+    bytes as f64 / 1024.0
+}
+
+/// Runs a system for a number of frames, returning how long it took in
+///  nanoseconds.
+fn run_frames(cpu : &mut CPU, frames : u32) -> u64 {
+    let start = Instant::now();
+
+    for _ in 0 .. frames {
+        cpu.run(&mut None);
+
+        // Keep the sample buffer from filling up, as a frontend would
+        cpu.mem.sound.take_samples();
+    }
+
+    nanos(start.elapsed())
+}
+
+/// Runs a ROM for a number of frames, printing a report. Battery saves are read,
+///  but never written.
+pub fn run(path : &Path, frames : u32) -> Result<(), String> {
+    let mut cpu = loader::load_game(path, None)?;
+
+    println!("Running {} for {} frames...", cpu.mem.rom.name, frames);
+
+    let elapsed = run_frames(&mut cpu, frames);
+    let frames_per_sec = frames as f64 / seconds(elapsed);
+
+    println!("Ran {} frames in {:.2}s: {:.1} frames/sec ({:.0}% of full speed)",
+             frames, seconds(elapsed), frames_per_sec,
+             frames_per_sec / GAMEBOY_FRAMERATE * 100.0);
+
+    #[cfg(feature = "profiling")]
+    profile(path, frames)?;
+
+    let report = memory_report(&cpu);
+    println!("Memory: {:.1} KB on the heap, {:.1} KB per save state",
+             kilobytes(report.total()), kilobytes(report.savestate));
+
+    for &(name, size) in &[("Framebuffer", report.framebuffer), ("Video RAM", report.video_ram),
+                           ("Work RAM", report.work_ram), ("Cart RAM", report.cart_ram),
+                           ("ROM", report.rom_copy), ("Audio", report.audio_buffers),
+                           ("Other", report.other)] {
+        println!("  {:<12} {:>8.1} KB", name, kilobytes(size));
+    }
+
+    Ok(())
+}
+
+/// Runs the ROM again from the start with the profiler attached, printing where
+///  the time went. This is a separate pass, as profiling slows down emulation.
+#[cfg(feature = "profiling")]
+fn profile(path : &Path, frames : u32) -> Result<(), String> {
+    let mut cpu = loader::load_game(path, None)?;
+
+    start_profiling(&mut cpu, clock::now);
+
+    let elapsed = run_frames(&mut cpu, frames);
+    println!("Profiled run took {:.2}s:", seconds(elapsed));
+
+    let mut accounted = 0;
+
+    for timing in subsystem_timings(&cpu) {
+        accounted += timing.time;
+
+        println!("  {:<6} {:>8.3}s {:>5.1}% {:>14} {}", timing.subsystem.name(),
+                 seconds(timing.time), timing.time as f64 / elapsed as f64 * 100.0,
+                 timing.count, timing.subsystem.unit());
+    }
+
+    // Sample handling, plus the overhead of the profiler itself
+    let other = elapsed.saturating_sub(accounted);
+    println!("  {:<6} {:>8.3}s {:>5.1}%", "Other", seconds(other),
+             other as f64 / elapsed as f64 * 100.0);
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+/**
+ * bindings.rs
+ *
+ * Maps host keys to Gameboy buttons and frontend hotkeys.
+**/
+
+use glutin::VirtualKeyCode;
+
+use oxidgb_core::input::GameboyButton;
+
+/// Names used for keys in the config file.
+static KEY_NAMES : [(&'static str, VirtualKeyCode); 90] = [
+    ("A", VirtualKeyCode::A),
+    ("B", VirtualKeyCode::B),
+    ("C", VirtualKeyCode::C),
+    ("D", VirtualKeyCode::D),
+    ("E", VirtualKeyCode::E),
+    ("F", VirtualKeyCode::F),
+    ("G", VirtualKeyCode::G),
+    ("H", VirtualKeyCode::H),
+    ("I", VirtualKeyCode::I),
+    ("J", VirtualKeyCode::J),
+    ("K", VirtualKeyCode::K),
+    ("L", VirtualKeyCode::L),
+    ("M", VirtualKeyCode::M),
+    ("N", VirtualKeyCode::N),
+    ("O", VirtualKeyCode::O),
+    ("P", VirtualKeyCode::P),
+    ("Q", VirtualKeyCode::Q),
+    ("R", VirtualKeyCode::R),
+    ("S", VirtualKeyCode::S),
+    ("T", VirtualKeyCode::T),
+    ("U", VirtualKeyCode::U),
+    ("V", VirtualKeyCode::V),
+    ("W", VirtualKeyCode::W),
+    ("X", VirtualKeyCode::X),
+    ("Y", VirtualKeyCode::Y),
+    ("Z", VirtualKeyCode::Z),
+    ("0", VirtualKeyCode::Key0),
+    ("1", VirtualKeyCode::Key1),
+    ("2", VirtualKeyCode::Key2),
+    ("3", VirtualKeyCode::Key3),
+    ("4", VirtualKeyCode::Key4),
+    ("5", VirtualKeyCode::Key5),
+    ("6", VirtualKeyCode::Key6),
+    ("7", VirtualKeyCode::Key7),
+    ("8", VirtualKeyCode::Key8),
+    ("9", VirtualKeyCode::Key9),
+    ("F1", VirtualKeyCode::F1),
+    ("F2", VirtualKeyCode::F2),
+    ("F3", VirtualKeyCode::F3),
+    ("F4", VirtualKeyCode::F4),
+    ("F5", VirtualKeyCode::F5),
+    ("F6", VirtualKeyCode::F6),
+    ("F7", VirtualKeyCode::F7),
+    ("F8", VirtualKeyCode::F8),
+    ("F9", VirtualKeyCode::F9),
+    ("F10", VirtualKeyCode::F10),
+    ("F11", VirtualKeyCode::F11),
+    ("F12", VirtualKeyCode::F12),
+    ("Up", VirtualKeyCode::Up),
+    ("Down", VirtualKeyCode::Down),
+    ("Left", VirtualKeyCode::Left),
+    ("Right", VirtualKeyCode::Right),
+    ("Return", VirtualKeyCode::Return),
+    ("Space", VirtualKeyCode::Space),
+    ("Tab", VirtualKeyCode::Tab),
+    ("Escape", VirtualKeyCode::Escape),
+    ("Back", VirtualKeyCode::Back),
+    ("LShift", VirtualKeyCode::LShift),
+    ("RShift", VirtualKeyCode::RShift),
+    ("LControl", VirtualKeyCode::LControl),
+    ("RControl", VirtualKeyCode::RControl),
+    ("LAlt", VirtualKeyCode::LAlt),
+    ("RAlt", VirtualKeyCode::RAlt),
+    ("Comma", VirtualKeyCode::Comma),
+    ("Period", VirtualKeyCode::Period),
+    ("Slash", VirtualKeyCode::Slash),
+    ("Semicolon", VirtualKeyCode::Semicolon),
+    ("Apostrophe", VirtualKeyCode::Apostrophe),
+    ("LBracket", VirtualKeyCode::LBracket),
+    ("RBracket", VirtualKeyCode::RBracket),
+    ("Minus", VirtualKeyCode::Minus),
+    ("Equals", VirtualKeyCode::Equals),
+    ("Backslash", VirtualKeyCode::Backslash),
+    ("Grave", VirtualKeyCode::Grave),
+    ("Insert", VirtualKeyCode::Insert),
+    ("Delete", VirtualKeyCode::Delete),
+    ("Home", VirtualKeyCode::Home),
+    ("End", VirtualKeyCode::End),
+    ("PageUp", VirtualKeyCode::PageUp),
+    ("PageDown", VirtualKeyCode::PageDown),
+    ("Numpad0", VirtualKeyCode::Numpad0),
+    ("Numpad1", VirtualKeyCode::Numpad1),
+    ("Numpad2", VirtualKeyCode::Numpad2),
+    ("Numpad3", VirtualKeyCode::Numpad3),
+    ("Numpad4", VirtualKeyCode::Numpad4),
+    ("Numpad5", VirtualKeyCode::Numpad5),
+    ("Numpad6", VirtualKeyCode::Numpad6),
+    ("Numpad7", VirtualKeyCode::Numpad7),
+    ("Numpad8", VirtualKeyCode::Numpad8),
+    ("Numpad9", VirtualKeyCode::Numpad9),
+];
+
+/// Parses a key from its name in the config file (case-insensitive).
+pub fn parse_key(name : &str) -> Option<VirtualKeyCode> {
+    KEY_NAMES.iter()
+        .find(|x| x.0.eq_ignore_ascii_case(name))
+        .map(|x| x.1)
+}
+
+/// Things a key can be bound to, other than Gameboy buttons.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Hotkey {
+    FastForward,
+    Pause,
+    FrameAdvance
+}
+
+/// What a key does when pressed.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Button(GameboyButton),
+    Hotkey(Hotkey)
+}
+
+/// A resolved set of key bindings.
+pub struct Bindings {
+    bindings : Vec<(VirtualKeyCode, Action)>
+}
+
+impl Bindings {
+    /// Returns what a key is bound to, if anything.
+    pub fn lookup(&self, key : VirtualKeyCode) -> Option<&Action> {
+        self.bindings.iter()
+            .find(|x| x.0 == key)
+            .map(|x| &x.1)
+    }
+
+    /// Binds a key by name, warning (and keeping the default) if it is unknown.
+    fn bind(&mut self, name : &str, default : VirtualKeyCode, action : Action) {
+        let key = match parse_key(name) {
+            Some(key) => key,
+            None => {
+                warn!("Unknown key {:?} for {:?}, using {:?}", name, action, default);
+                default
+            }
+        };
+
+        self.bindings.push((key, action));
+    }
+
+    pub fn build(config : &InputConfig) -> Bindings {
+        let mut bindings = Bindings {
+            bindings : Vec::new()
+        };
+
+        bindings.bind(&config.up, VirtualKeyCode::Up, Action::Button(GameboyButton::UP));
+        bindings.bind(&config.down, VirtualKeyCode::Down, Action::Button(GameboyButton::DOWN));
+        bindings.bind(&config.left, VirtualKeyCode::Left, Action::Button(GameboyButton::LEFT));
+        bindings.bind(&config.right, VirtualKeyCode::Right, Action::Button(GameboyButton::RIGHT));
+        bindings.bind(&config.a, VirtualKeyCode::X, Action::Button(GameboyButton::A));
+        bindings.bind(&config.b, VirtualKeyCode::Z, Action::Button(GameboyButton::B));
+        bindings.bind(&config.start, VirtualKeyCode::S, Action::Button(GameboyButton::START));
+        bindings.bind(&config.select, VirtualKeyCode::A, Action::Button(GameboyButton::SELECT));
+
+        bindings.bind(&config.fast_forward, VirtualKeyCode::Tab,
+                      Action::Hotkey(Hotkey::FastForward));
+        bindings.bind(&config.pause, VirtualKeyCode::P, Action::Hotkey(Hotkey::Pause));
+        bindings.bind(&config.frame_advance, VirtualKeyCode::N,
+                      Action::Hotkey(Hotkey::FrameAdvance));
+
+        bindings
+    }
+}
+
+/// Key bindings, as stored in the config file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct InputConfig {
+    pub up : String,
+    pub down : String,
+    pub left : String,
+    pub right : String,
+    pub a : String,
+    pub b : String,
+    pub start : String,
+    pub select : String,
+
+    pub fast_forward : String,
+    pub pause : String,
+    pub frame_advance : String
+}
+
+impl Default for InputConfig {
+    fn default() -> InputConfig {
+        InputConfig {
+            up : "Up".to_string(),
+            down : "Down".to_string(),
+            left : "Left".to_string(),
+            right : "Right".to_string(),
+            a : "X".to_string(),
+            b : "Z".to_string(),
+            start : "S".to_string(),
+            select : "A".to_string(),
+
+            fast_forward : "Tab".to_string(),
+            pause : "P".to_string(),
+            frame_advance : "N".to_string()
+        }
+    }
+}
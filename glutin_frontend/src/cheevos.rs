@@ -0,0 +1,185 @@
+/**
+ * cheevos.rs
+ *
+ * Evaluates RetroAchievements-style achievements with rcheevos, which must be
+ *  built as a library and available to the linker. Achievements are read from a
+ *  local file rather than fetched from a server, one per line as
+ *  `ID:MEMADDR:Title`, where MEMADDR is in rcheevos' condition syntax.
+**/
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fs;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::ptr;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+
+use oxidgb_core::achievements;
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::mem::GBMemory;
+
+/// rc_runtime_event_t's type when an achievement's conditions are met.
+const RC_RUNTIME_EVENT_ACHIEVEMENT_TRIGGERED : u8 = 3;
+
+const RC_OK : c_int = 0;
+
+/// Opaque - only ever handled through a pointer from rc_runtime_alloc.
+#[repr(C)]
+struct RcRuntime {
+    _private : [u8; 0]
+}
+
+#[repr(C)]
+struct RcRuntimeEvent {
+    id : u32,
+    value : i32,
+    event_type : u8
+}
+
+type PeekFn = extern "C" fn(address : u32, num_bytes : u32, ud : *mut c_void) -> u32;
+type EventHandlerFn = extern "C" fn(event : *const RcRuntimeEvent);
+
+#[link(name = "rcheevos")]
+extern "C" {
+    fn rc_runtime_alloc() -> *mut RcRuntime;
+    fn rc_runtime_destroy(runtime : *mut RcRuntime);
+    fn rc_runtime_activate_achievement(runtime : *mut RcRuntime, id : u32, memaddr : *const c_char,
+                                       lua : *mut c_void, funcs_idx : c_int) -> c_int;
+    fn rc_runtime_do_frame(runtime : *mut RcRuntime, event_handler : EventHandlerFn,
+                           peek : PeekFn, ud : *mut c_void, lua : *mut c_void);
+}
+
+// rcheevos' event handler has no user data, so events are collected here
+thread_local!(static TRIGGERED : RefCell<Vec<u32>> = RefCell::new(Vec::new()));
+
+extern "C" fn handle_event(event : *const RcRuntimeEvent) {
+    let event = unsafe { &*event };
+
+    if event.event_type == RC_RUNTIME_EVENT_ACHIEVEMENT_TRIGGERED {
+        TRIGGERED.with(|triggered| triggered.borrow_mut().push(event.id));
+    }
+}
+
+extern "C" fn peek(address : u32, num_bytes : u32, ud : *mut c_void) -> u32 {
+    let mem = unsafe { &*(ud as *const GBMemory) };
+    achievements::peek(mem, address, num_bytes)
+}
+
+struct Achievement {
+    id : u32,
+    title : String
+}
+
+/// Owns a rcheevos runtime, along with the achievements which haven't been
+///  unlocked yet.
+struct Runtime {
+    runtime : *mut RcRuntime,
+    locked : Vec<Achievement>,
+    unlocked : Sender<String>
+}
+
+impl Runtime {
+    fn do_frame(&mut self, mem : &GBMemory) {
+        unsafe {
+            rc_runtime_do_frame(self.runtime, handle_event, peek,
+                                mem as *const GBMemory as *mut c_void, ptr::null_mut());
+        }
+
+        let triggered : Vec<u32> = TRIGGERED.with(|triggered| triggered.borrow_mut().drain(..).collect());
+
+        for id in triggered {
+            if let Some(position) = self.locked.iter().position(|x| x.id == id) {
+                let achievement = self.locked.remove(position);
+                info!("Achievement unlocked: {} ({})", achievement.title, id);
+
+                // The receiver only goes away when the emulator is closing
+                let _ = self.unlocked.send(achievement.title);
+            }
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        unsafe {
+            rc_runtime_destroy(self.runtime);
+        }
+    }
+}
+
+/// Parses an achievements file. Blank lines and lines starting with "#" are
+///  ignored.
+fn parse_achievements(data : &str) -> Result<Vec<(Achievement, String)>, String> {
+    let mut parsed = Vec::new();
+
+    for (number, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.splitn(3, ':');
+        let id = parts.next().and_then(|x| x.trim().parse().ok())
+            .ok_or_else(|| format!("line {}: invalid achievement ID", number + 1))?;
+        let memaddr = parts.next()
+            .ok_or_else(|| format!("line {}: missing conditions", number + 1))?;
+        let title = parts.next().unwrap_or("").trim();
+
+        parsed.push((Achievement {
+            id,
+            title : if title.is_empty() { format!("#{}", id) } else { title.to_string() }
+        }, memaddr.trim().trim_matches('"').to_string()));
+    }
+
+    Ok(parsed)
+}
+
+/// Loads achievements from a file, and evaluates them at the end of each frame
+///  the system runs. Returns a channel which receives the titles of
+///  achievements as they are unlocked.
+pub fn attach(cpu : &mut CPU, path : &Path) -> Result<Receiver<String>, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+    let parsed = parse_achievements(&data)
+        .map_err(|why| format!("{}: {}", path.display(), why))?;
+
+    let runtime = unsafe { rc_runtime_alloc() };
+    if runtime.is_null() {
+        return Err("couldn't allocate a rcheevos runtime".to_string());
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut runtime = Runtime {
+        runtime,
+        locked : Vec::new(),
+        unlocked : sender
+    };
+
+    for (achievement, memaddr) in parsed {
+        let memaddr = CString::new(memaddr)
+            .map_err(|_| format!("achievement {} has a NUL in its conditions", achievement.id))?;
+
+        let result = unsafe {
+            rc_runtime_activate_achievement(runtime.runtime, achievement.id, memaddr.as_ptr(),
+                                            ptr::null_mut(), 0)
+        };
+
+        if result != RC_OK {
+            warn!("Ignoring achievement {}: rcheevos error {}", achievement.id, result);
+            continue;
+        }
+
+        runtime.locked.push(achievement);
+    }
+
+    info!("Loaded {} achievement(s) from {}", runtime.locked.len(), path.display());
+
+    cpu.mem.hooks.on_frame(Box::new(move |mem, _| runtime.do_frame(mem)));
+
+    Ok(receiver)
+}
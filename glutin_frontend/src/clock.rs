@@ -0,0 +1,20 @@
+/**
+ * clock.rs
+ *
+ * A monotonic clock for the core's profiler and tracer, which can't read the
+ *  time themselves.
+**/
+
+use std::time::Duration;
+use std::time::Instant;
+
+thread_local!(static CLOCK_START : Instant = Instant::now());
+
+pub fn nanos(duration : Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
+
+/// Nanoseconds since the clock was first used on this thread.
+pub fn now() -> u64 {
+    CLOCK_START.with(|start| nanos(start.elapsed()))
+}
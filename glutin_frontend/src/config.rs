@@ -0,0 +1,429 @@
+/**
+ * config.rs
+ *
+ * User configuration, including per-game overrides.
+**/
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use dirs;
+use toml;
+
+use oxidgb_core::cheats;
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::rom::GameROM;
+
+use audio;
+use bindings::InputConfig;
+use limiter::FastForwardCap;
+use video::ScalingMode;
+
+/// How many recently opened ROMs are remembered.
+const MAX_RECENT_ROMS : usize = 10;
+
+/// The hardware model to emulate. This is currently advisory: only the DMG is
+///  emulated, so asking for a CGB just logs a warning.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Model {
+    /// Picks based on what the cartridge declares.
+    Auto,
+    Dmg,
+    Cgb
+}
+
+/// Tradeoffs the renderer is allowed to make.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Accuracy {
+    /// Behave like hardware, including the 10 sprites per line limit.
+    Accurate,
+    /// Draw every sprite, avoiding flicker.
+    Relaxed
+}
+
+/// A cheat code, and if it should currently be applied.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheatEntry {
+    pub code : String,
+    #[serde(default = "default_enabled")]
+    pub enabled : bool
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Settings which control how a game is emulated. These can be overridden on a
+///  per-game basis.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GameSettings {
+    /// The four shades of the screen, lightest first, as "#RRGGBB".
+    pub palette : Vec<String>,
+    pub model : Model,
+    pub accuracy : Accuracy,
+    pub cheats : Vec<CheatEntry>
+}
+
+impl Default for GameSettings {
+    fn default() -> GameSettings {
+        GameSettings {
+            // BGB palette, matching the core
+            palette : vec!["#E0F8D0".to_string(), "#88C070".to_string(),
+                           "#346856".to_string(), "#081820".to_string()],
+            model : Model::Auto,
+            accuracy : Accuracy::Accurate,
+            cheats : Vec::new()
+        }
+    }
+}
+
+/// A per-game config file. Anything left out falls back to the global settings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GameOverrides {
+    pub palette : Option<Vec<String>>,
+    pub model : Option<Model>,
+    pub accuracy : Option<Accuracy>,
+    pub cheats : Option<Vec<CheatEntry>>
+}
+
+impl GameOverrides {
+    /// Merges these overrides over the top of a set of global settings.
+    pub fn merge(&self, base : &GameSettings) -> GameSettings {
+        GameSettings {
+            palette : self.palette.clone().unwrap_or_else(|| base.palette.clone()),
+            model : self.model.unwrap_or(base.model),
+            accuracy : self.accuracy.unwrap_or(base.accuracy),
+            cheats : self.cheats.clone().unwrap_or_else(|| base.cheats.clone())
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct VideoConfig {
+    pub scaling : ScalingMode,
+    pub fullscreen : bool,
+    /// The initial size of the window, as a multiple of the screen size.
+    pub window_scale : u32
+}
+
+impl Default for VideoConfig {
+    fn default() -> VideoConfig {
+        VideoConfig {
+            scaling : ScalingMode::Integer,
+            fullscreen : false,
+            window_scale : 2
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub enabled : bool,
+    /// Output volume, from 0 to 1.
+    pub volume : f32,
+    /// The name of the output device. If unset, the system default is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device : Option<String>,
+    /// How much audio to keep queued. Lower values reduce lag, but may crackle.
+    pub latency_ms : u64
+}
+
+impl Default for AudioConfig {
+    fn default() -> AudioConfig {
+        AudioConfig {
+            enabled : true,
+            volume : 1.0,
+            device : None,
+            latency_ms : audio::DEFAULT_LATENCY_MS
+        }
+    }
+}
+
+/// A recently opened ROM, with enough detail to show it before it is loaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecentRom {
+    pub path : PathBuf,
+    pub title : String,
+    /// The game's checksum key, which its thumbnail is stored under.
+    pub id : String
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct DirectoryConfig {
+    /// Where the file dialog starts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roms : Option<PathBuf>,
+    /// Where battery saves are written. If unset, saves sit alongside the ROM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saves : Option<PathBuf>
+}
+
+/// The global configuration file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    // Plain values must come before tables for TOML
+    pub fast_forward : FastForwardCap,
+
+    pub video : VideoConfig,
+    pub audio : AudioConfig,
+    pub input : InputConfig,
+    pub directories : DirectoryConfig,
+    /// Global settings for games, which can be overridden per game.
+    pub game : GameSettings,
+    /// Most recently opened first.
+    pub recent : Vec<RecentRom>
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            fast_forward : FastForwardCap::Quadruple,
+
+            video : VideoConfig::default(),
+            audio : AudioConfig::default(),
+            input : InputConfig::default(),
+            directories : DirectoryConfig::default(),
+            game : GameSettings::default(),
+            recent : Vec::new()
+        }
+    }
+}
+
+impl Config {
+    /// Moves a ROM to the top of the recently opened list.
+    pub fn add_recent_rom(&mut self, path : &Path, rom : &GameROM) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        self.recent.retain(|x| x.path != path);
+        self.recent.insert(0, RecentRom {
+            path,
+            title : rom.name.clone(),
+            id : game_id(rom)
+        });
+        self.recent.truncate(MAX_RECENT_ROMS);
+    }
+
+    /// Drops recent ROMs which no longer exist on disk.
+    pub fn prune_recent_roms(&mut self) {
+        self.recent.retain(|x| x.path.exists());
+    }
+
+    /// Loads the config from a file, falling back to defaults if it is missing.
+    ///  Fails if it exists but couldn't be read or parsed.
+    pub fn load(path : &Path) -> Result<Config, String> {
+        if !path.exists() {
+            info!("No config at {}, using defaults", path.display());
+            return Ok(Config::default());
+        }
+
+        let data = fs::read_to_string(path)
+            .map_err(|why| format!("couldn't read config {}: {}", path.display(), why))?;
+
+        let config = toml::from_str(&data)
+            .map_err(|why| format!("invalid config {}: {}", path.display(), why))?;
+
+        debug!("Loaded config from {}", path.display());
+        Ok(config)
+    }
+
+    /// Writes the config to a file, creating its directory if needed.
+    pub fn save(&self, path : &Path) {
+        let data = match toml::to_string_pretty(self) {
+            Ok(data) => data,
+            Err(why) => {
+                error!("couldn't serialize config: {}", why);
+                return;
+            }
+        };
+
+        if let Some(parent) = path.parent() {
+            if let Err(why) = fs::create_dir_all(parent) {
+                error!("couldn't create {}: {}", parent.display(), why);
+                return;
+            }
+        }
+
+        match fs::write(path, data) {
+            Ok(_) => debug!("Wrote config to {}", path.display()),
+            Err(why) => error!("couldn't write config {}: {}", path.display(), why)
+        }
+    }
+}
+
+/// Parses a "#RRGGBB" colour.
+fn parse_color(value : &str) -> Option<[u8; 3]> {
+    let value = value.trim_left_matches('#');
+
+    if value.len() != 6 {
+        return None;
+    }
+
+    let mut color = [0u8; 3];
+    for i in 0 .. 3 {
+        color[i] = match u8::from_str_radix(&value[i * 2 .. i * 2 + 2], 16) {
+            Ok(component) => component,
+            Err(_) => return None
+        };
+    }
+
+    Some(color)
+}
+
+/// Parses a palette into the core's representation.
+pub fn parse_palette(palette : &[String]) -> Option<[u8; 4 * 3]> {
+    if palette.len() != 4 {
+        return None;
+    }
+
+    let mut output = [0u8; 4 * 3];
+
+    for (i, value) in palette.iter().enumerate() {
+        let color = parse_color(value)?;
+        output[i * 3 .. i * 3 + 3].copy_from_slice(&color);
+    }
+
+    Some(output)
+}
+
+/// Returns the directory which configuration is stored in.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("oxidgb"))
+}
+
+/// Returns where the global config file lives.
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Returns the directory which disposable data (such as thumbnails) is stored in.
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("oxidgb"))
+}
+
+/// Returns the key a game's files are stored under. Games are keyed by their
+///  header and global checksums, so renaming a ROM doesn't lose its settings.
+pub fn game_id(rom : &GameROM) -> String {
+    format!("{:02X}{:04X}", rom.header_checksum(), rom.global_checksum())
+}
+
+/// Returns where the overrides file for a game lives.
+pub fn overrides_path(rom : &GameROM) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("games").join(format!("{}.toml", game_id(rom))))
+}
+
+/// Returns where the thumbnail for a game lives, given its ID.
+pub fn thumbnail_path(id : &str) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("thumbnails").join(format!("{}.ppm", id)))
+}
+
+/// Loads the overrides for a game, if it has any.
+pub fn load_overrides(rom : &GameROM) -> Option<GameOverrides> {
+    let path = overrides_path(rom)?;
+
+    debug!("Looking for game overrides in {}", path.display());
+
+    if !path.exists() {
+        return None;
+    }
+
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(why) => {
+            warn!("couldn't read {}: {}", path.display(), why);
+            return None;
+        }
+    };
+
+    match toml::from_str(&data) {
+        Ok(overrides) => {
+            info!("Using game overrides from {}", path.display());
+            Some(overrides)
+        },
+        Err(why) => {
+            warn!("Ignoring invalid game overrides {}: {}", path.display(), why);
+            None
+        }
+    }
+}
+
+/// Adds a cheat to a game's overrides, so it is applied whenever the game is
+///  loaded. Cheats inherited from the global settings are kept.
+pub fn add_cheat(global : &GameSettings, rom : &GameROM, code : &str) -> Result<(), String> {
+    let code = code.trim();
+    if let Err(why) = cheats::parse(code) {
+        return Err(format!("invalid cheat {:?}: {:?}", code, why));
+    }
+
+    let path = overrides_path(rom).ok_or("no config directory is available")?;
+
+    let mut overrides = match load_overrides(rom) {
+        Some(overrides) => overrides,
+        None if path.exists() => return Err(format!("{} is invalid - not overwriting it",
+                                                    path.display())),
+        None => GameOverrides::default()
+    };
+
+    let mut cheats = overrides.cheats.take().unwrap_or_else(|| global.cheats.clone());
+    if cheats.iter().any(|x| x.code.eq_ignore_ascii_case(code)) {
+        return Ok(());
+    }
+
+    cheats.push(CheatEntry {
+        code : code.to_string(),
+        enabled : true
+    });
+    overrides.cheats = Some(cheats);
+
+    let data = toml::to_string_pretty(&overrides)
+        .map_err(|why| format!("couldn't serialize overrides: {}", why))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|why| format!("couldn't create {}: {}", parent.display(), why))?;
+    }
+
+    fs::write(&path, data).map_err(|why| format!("couldn't write {}: {}", path.display(), why))?;
+    info!("Added cheat {} to {}", code, path.display());
+
+    Ok(())
+}
+
+/// Resolves the settings for a game, merging any overrides over the global settings.
+pub fn game_settings(global : &GameSettings, rom : &GameROM) -> GameSettings {
+    match load_overrides(rom) {
+        Some(overrides) => overrides.merge(global),
+        None => global.clone()
+    }
+}
+
+/// Applies a game's settings to a freshly built system.
+pub fn apply_settings(cpu : &mut CPU, settings : &GameSettings) {
+    match parse_palette(&settings.palette) {
+        Some(palette) => cpu.mem.gpu.palette = palette,
+        None => warn!("Invalid palette {:?}, expected 4 \"#RRGGBB\" colours",
+                      settings.palette)
+    }
+
+    // CGB games run on a DMG where they can, so only an explicit request warns
+    if settings.model == Model::Cgb {
+        warn!("Gameboy Color emulation is not supported - running as a DMG");
+    }
+
+    cpu.mem.gpu.sprite_limit = settings.accuracy == Accuracy::Accurate;
+
+    cpu.mem.rom.cheats.clear();
+    for cheat in &settings.cheats {
+        if let Err(why) = cpu.mem.rom.cheats.add(&cheat.code, cheat.enabled) {
+            warn!("Ignoring invalid cheat {:?}: {:?}", cheat.code, why);
+        }
+    }
+}
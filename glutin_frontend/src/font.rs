@@ -0,0 +1,152 @@
+/**
+ * font.rs
+ *
+ * A tiny built-in 5x7 bitmap font for drawing text over the emulated frame.
+**/
+
+use oxidgb_core::gpu::PITCH;
+
+pub const GLYPH_WIDTH : usize = 5;
+pub const GLYPH_HEIGHT : usize = 7;
+
+/// Horizontal distance between the start of each character.
+pub const CHAR_ADVANCE : usize = GLYPH_WIDTH + 1;
+
+const SCREEN_WIDTH : usize = 160;
+const SCREEN_HEIGHT : usize = 144;
+
+/// Glyphs for ASCII 0x20 - 0x5F. Each row is 5 bits wide, MSB on the left.
+static GLYPHS : [[u8; GLYPH_HEIGHT]; 64] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x04, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+];
+
+/// Returns the glyph for a character. Lowercase is drawn as uppercase, and anything
+///  unknown as a '?'.
+fn glyph(c : char) -> &'static [u8; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+
+    if c >= ' ' && c <= '_' {
+        &GLYPHS[c as usize - 0x20]
+    } else {
+        &GLYPHS['?' as usize - 0x20]
+    }
+}
+
+/// Returns how many pixels wide a string will be when drawn.
+pub fn text_width(text : &str) -> usize {
+    let chars = text.chars().count();
+
+    if chars == 0 {
+        0
+    } else {
+        chars * CHAR_ADVANCE - 1
+    }
+}
+
+/// Darkens a rectangle of a RGB framebuffer, to give text something to sit on.
+pub fn shade_rect(buffer : &mut [u8], x : usize, y : usize, width : usize, height : usize) {
+    for row in y .. (y + height).min(SCREEN_HEIGHT) {
+        for col in x .. (x + width).min(SCREEN_WIDTH) {
+            let pos = (row * SCREEN_WIDTH + col) * PITCH;
+
+            for i in 0 .. PITCH {
+                buffer[pos + i] /= 3;
+            }
+        }
+    }
+}
+
+/// Draws a string onto a RGB framebuffer, clipping anything off-screen.
+pub fn draw_text(buffer : &mut [u8], x : usize, y : usize, text : &str, color : [u8; 3]) {
+    for (index, c) in text.chars().enumerate() {
+        let glyph = glyph(c);
+        let origin_x = x + index * CHAR_ADVANCE;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            let pixel_y = y + row;
+            if pixel_y >= SCREEN_HEIGHT {
+                break;
+            }
+
+            for col in 0 .. GLYPH_WIDTH {
+                let pixel_x = origin_x + col;
+                if pixel_x >= SCREEN_WIDTH || (bits >> (GLYPH_WIDTH - 1 - col)) & 0x1 == 0 {
+                    continue;
+                }
+
+                let pos = (pixel_y * SCREEN_WIDTH + pixel_x) * PITCH;
+                buffer[pos .. pos + PITCH].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Draws a string on a shaded backing box.
+pub fn draw_label(buffer : &mut [u8], x : usize, y : usize, text : &str, color : [u8; 3]) {
+    shade_rect(buffer, x.saturating_sub(2), y.saturating_sub(2),
+               text_width(text) + 4, GLYPH_HEIGHT + 4);
+    draw_text(buffer, x, y, text, color);
+}
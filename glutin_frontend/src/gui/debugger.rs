@@ -0,0 +1,301 @@
+/**
+ * gui/debugger.rs
+ *
+ * Windows for the graphical debugger: controls, registers, disassembly,
+ *  memory and breakpoints. VRAM viewers, RAM search and the profiler (in
+ *  profiling builds) are toggled from the controls.
+**/
+
+use egui;
+use egui::Color32;
+use egui::RichText;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::BreakReason;
+use oxidgb_core::debug::DebugController;
+use oxidgb_core::debug::disasm;
+use oxidgb_core::debug::parse_address;
+
+#[cfg(feature = "profiling")]
+use gui::profiler::ProfilerWindow;
+use gui::scanner::RamSearch;
+use gui::viewers::VramViewers;
+
+/// How many instructions are shown either side of PC.
+const DISASSEMBLY_CONTEXT : usize = 12;
+
+/// How many rows of 16 bytes the memory view shows.
+const MEMORY_ROWS : u16 = 16;
+
+const PC_COLOR : Color32 = Color32::from_rgb(255, 220, 90);
+const BREAKPOINT_COLOR : Color32 = Color32::from_rgb(230, 70, 70);
+
+pub struct DebuggerWindows {
+    /// Where the memory view starts.
+    memory_base : u16,
+    memory_input : String,
+    breakpoint_input : String,
+
+    viewers : VramViewers,
+    ram_search : RamSearch,
+    #[cfg(feature = "profiling")]
+    profiler : ProfilerWindow
+}
+
+impl DebuggerWindows {
+    /// Shows all debugger windows for a frame.
+    pub fn show(&mut self, ctx : &egui::Context, cpu : &mut CPU,
+                controller : &mut DebugController) {
+        self.show_controls(ctx, cpu, controller);
+        self.show_registers(ctx, cpu);
+        self.show_disassembly(ctx, cpu, controller);
+        self.show_memory(ctx, cpu);
+        self.show_breakpoints(ctx, controller);
+        self.viewers.show(ctx, &cpu.mem.gpu);
+        self.ram_search.show(ctx, cpu);
+        #[cfg(feature = "profiling")]
+        self.profiler.show(ctx, cpu);
+    }
+
+    fn show_controls(&mut self, ctx : &egui::Context, cpu : &CPU,
+                     controller : &mut DebugController) {
+        egui::Window::new("Controls")
+            .default_pos([8.0, 8.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                let paused = controller.is_paused();
+
+                ui.horizontal(|ui| {
+                    if paused {
+                        if ui.button("Run").clicked() {
+                            controller.resume();
+                        }
+                    } else if ui.button("Pause").clicked() {
+                        controller.pause();
+                    }
+
+                    if ui.add_enabled(paused, egui::Button::new("Step")).clicked() {
+                        controller.step(1);
+                    }
+
+                    if ui.add_enabled(paused, egui::Button::new("Step over")).clicked() {
+                        controller.step_over(cpu);
+                    }
+                });
+
+                let status = if !paused {
+                    String::from("Running")
+                } else {
+                    match controller.last_break() {
+                        Some(BreakReason::Breakpoint(address)) =>
+                            format!("Hit breakpoint at ${:04X}", address),
+                        _ => format!("Paused at ${:04X}", cpu.regs.pc)
+                    }
+                };
+
+                ui.label(status);
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.viewers.show_tiles, "Tiles");
+                    ui.checkbox(&mut self.viewers.show_tilemaps, "Tile maps");
+                    ui.checkbox(&mut self.viewers.show_sprites, "Sprites");
+                    ui.checkbox(&mut self.viewers.show_palettes, "Palettes");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.ram_search.show, "RAM search");
+                    #[cfg(feature = "profiling")]
+                    ui.checkbox(&mut self.profiler.show, "Profiler");
+                });
+            });
+    }
+
+    fn show_registers(&mut self, ctx : &egui::Context, cpu : &CPU) {
+        egui::Window::new("Registers")
+            .default_pos([8.0, 96.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("registers").num_columns(4).striped(true).show(ui, |ui| {
+                    let regs = &cpu.regs;
+
+                    ui.monospace("AF");
+                    ui.monospace(format!("{:04X}", regs.get_af()));
+                    ui.monospace("BC");
+                    ui.monospace(format!("{:04X}", regs.get_bc()));
+                    ui.end_row();
+
+                    ui.monospace("DE");
+                    ui.monospace(format!("{:04X}", regs.get_de()));
+                    ui.monospace("HL");
+                    ui.monospace(format!("{:04X}", regs.get_hl()));
+                    ui.end_row();
+
+                    ui.monospace("SP");
+                    ui.monospace(format!("{:04X}", regs.sp));
+                    ui.monospace("PC");
+                    ui.monospace(format!("{:04X}", regs.pc));
+                    ui.end_row();
+
+                    ui.monospace("IE");
+                    ui.monospace(format!("{:02X}", cpu.mem.interrupt_reg));
+                    ui.monospace("IF");
+                    ui.monospace(format!("{:02X}", cpu.mem.ioregs.iflag));
+                    ui.end_row();
+
+                    ui.monospace("LCDC");
+                    ui.monospace(format!("{:02X}", cpu.mem.gpu.lcdc));
+                    ui.monospace("LY");
+                    ui.monospace(format!("{:02X}", cpu.mem.gpu.current_line));
+                    ui.end_row();
+                });
+
+                let regs = &cpu.regs;
+                let flag = |set : bool, name : &'static str| if set { name } else { "-" };
+
+                ui.monospace(format!("Flags: {}{}{}{}  IME: {}  {}",
+                                     flag(regs.get_flag_z(), "Z"), flag(regs.get_flag_n(), "N"),
+                                     flag(regs.get_flag_h(), "H"), flag(regs.get_flag_c(), "C"),
+                                     if cpu.interrupts_enabled { "on" } else { "off" },
+                                     if cpu.halted { "HALT" } else if cpu.stopped { "STOP" } else { "" }));
+            });
+    }
+
+    fn show_disassembly(&mut self, ctx : &egui::Context, cpu : &CPU,
+                        controller : &mut DebugController) {
+        egui::Window::new("Disassembly")
+            .default_pos([260.0, 8.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                let pc = cpu.regs.pc;
+                let instructions = disasm::disassemble_around(|x| cpu.mem.debug_read(x), pc,
+                                                              DISASSEMBLY_CONTEXT,
+                                                              DISASSEMBLY_CONTEXT);
+
+                ui.label("Click an instruction to toggle a breakpoint.");
+
+                for instruction in instructions {
+                    let mut bytes = String::new();
+                    for i in 0 .. 3 {
+                        if i < instruction.length as usize {
+                            bytes.push_str(&format!("{:02X} ", instruction.bytes[i]));
+                        } else {
+                            bytes.push_str("   ");
+                        }
+                    }
+
+                    let breakpoint = controller.has_breakpoint(instruction.address);
+                    let line = format!("{} {:04X}  {} {}",
+                                       if breakpoint { "●" } else { " " },
+                                       instruction.address, bytes, instruction.text);
+
+                    let mut text = RichText::new(line).monospace();
+                    if instruction.address == pc {
+                        text = text.color(PC_COLOR);
+                    } else if breakpoint {
+                        text = text.color(BREAKPOINT_COLOR);
+                    }
+
+                    if ui.selectable_label(instruction.address == pc, text).clicked() {
+                        controller.toggle_breakpoint(instruction.address);
+                    }
+                }
+            });
+    }
+
+    fn show_memory(&mut self, ctx : &egui::Context, cpu : &CPU) {
+        egui::Window::new("Memory")
+            .default_pos([8.0, 300.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Address:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.memory_input)
+                        .desired_width(60.0));
+
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        if let Some(address) = parse_address(&self.memory_input) {
+                            self.memory_base = address & 0xFFF0;
+                        }
+                    }
+
+                    if ui.button("<<").clicked() {
+                        self.memory_base = self.memory_base.wrapping_sub(MEMORY_ROWS * 16);
+                    }
+                    if ui.button(">>").clicked() {
+                        self.memory_base = self.memory_base.wrapping_add(MEMORY_ROWS * 16);
+                    }
+                });
+
+                for row in 0 .. MEMORY_ROWS {
+                    let address = self.memory_base.wrapping_add(row * 16);
+
+                    let mut hex = String::new();
+                    let mut ascii = String::new();
+                    for i in 0 .. 16 {
+                        let value = cpu.mem.debug_read(address.wrapping_add(i));
+                        hex.push_str(&format!("{:02X} ", value));
+                        ascii.push(if value >= 0x20 && value < 0x7F { value as char } else { '.' });
+                    }
+
+                    ui.monospace(format!("{:04X}  {} {}", address, hex, ascii));
+                }
+            });
+    }
+
+    fn show_breakpoints(&mut self, ctx : &egui::Context, controller : &mut DebugController) {
+        egui::Window::new("Breakpoints")
+            .default_pos([260.0, 460.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.breakpoint_input)
+                        .desired_width(60.0));
+
+                    let submitted = response.lost_focus()
+                        && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    if ui.button("Add").clicked() || submitted {
+                        match parse_address(&self.breakpoint_input) {
+                            Some(address) => {
+                                if !controller.has_breakpoint(address) {
+                                    controller.toggle_breakpoint(address);
+                                }
+                                self.breakpoint_input.clear();
+                            },
+                            None => warn!("Invalid breakpoint address: {:?}", self.breakpoint_input)
+                        }
+                    }
+                });
+
+                let mut removed = None;
+
+                for &address in &controller.breakpoints {
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("${:04X}", address));
+                        if ui.small_button("Remove").clicked() {
+                            removed = Some(address);
+                        }
+                    });
+                }
+
+                if let Some(address) = removed {
+                    controller.toggle_breakpoint(address);
+                }
+            });
+    }
+
+    pub fn build() -> DebuggerWindows {
+        DebuggerWindows {
+            memory_base : 0xC000,
+            memory_input : String::from("C000"),
+            breakpoint_input : String::new(),
+
+            viewers : VramViewers::build(),
+            ram_search : RamSearch::build(),
+            #[cfg(feature = "profiling")]
+            profiler : ProfilerWindow::build()
+        }
+    }
+}
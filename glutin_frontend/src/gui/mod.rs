@@ -0,0 +1,212 @@
+/**
+ * gui/mod.rs
+ *
+ * An egui overlay drawn over the emulated screen, hosting the graphical debugger.
+**/
+
+mod debugger;
+mod painter;
+mod scanner;
+mod viewers;
+
+#[cfg(feature = "profiling")]
+mod profiler;
+
+use std::time::Instant;
+
+use glutin;
+use glutin::ElementState;
+use glutin::MouseButton;
+use glutin::MouseScrollDelta;
+use glutin::VirtualKeyCode;
+use glutin::WindowEvent;
+
+use egui;
+use egui::Event;
+use egui::Key;
+use egui::Modifiers;
+use egui::PointerButton;
+use egui::Pos2;
+use egui::RawInput;
+use egui::Rect;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::DebugController;
+
+use gui::debugger::DebuggerWindows;
+use gui::painter::Painter;
+
+/// How many points a single scroll wheel "line" moves.
+const SCROLL_LINE_POINTS : f32 = 24.0;
+
+/// Translates a key into one egui understands, if it cares about it.
+fn translate_key(key : VirtualKeyCode) -> Option<Key> {
+    Some(match key {
+        VirtualKeyCode::Up => Key::ArrowUp,
+        VirtualKeyCode::Down => Key::ArrowDown,
+        VirtualKeyCode::Left => Key::ArrowLeft,
+        VirtualKeyCode::Right => Key::ArrowRight,
+        VirtualKeyCode::Return => Key::Enter,
+        VirtualKeyCode::Back => Key::Backspace,
+        VirtualKeyCode::Delete => Key::Delete,
+        VirtualKeyCode::Tab => Key::Tab,
+        VirtualKeyCode::Escape => Key::Escape,
+        VirtualKeyCode::Home => Key::Home,
+        VirtualKeyCode::End => Key::End,
+        VirtualKeyCode::PageUp => Key::PageUp,
+        VirtualKeyCode::PageDown => Key::PageDown,
+        VirtualKeyCode::A => Key::A,
+        VirtualKeyCode::C => Key::C,
+        VirtualKeyCode::V => Key::V,
+        VirtualKeyCode::X => Key::X,
+        VirtualKeyCode::Z => Key::Z,
+        _ => return None
+    })
+}
+
+fn translate_modifiers(modifiers : glutin::ModifiersState) -> Modifiers {
+    Modifiers {
+        alt : modifiers.alt,
+        ctrl : modifiers.ctrl,
+        shift : modifiers.shift,
+        mac_cmd : cfg!(target_os = "macos") && modifiers.logo,
+        command : if cfg!(target_os = "macos") { modifiers.logo } else { modifiers.ctrl }
+    }
+}
+
+pub struct DebugOverlay {
+    /// If the overlay is currently shown. Input only goes to egui while visible.
+    pub visible : bool,
+
+    context : egui::Context,
+    painter : Painter,
+    windows : DebuggerWindows,
+
+    /// Input collected since the last frame.
+    events : Vec<Event>,
+    modifiers : Modifiers,
+    pointer_pos : Pos2,
+    pixels_per_point : f32,
+    start_time : Instant
+}
+
+impl DebugOverlay {
+    /// Passes a window event to egui. Returns true if egui consumed it, and it
+    ///  shouldn't be handled by the emulator.
+    pub fn handle_event(&mut self, event : &WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
+
+        let ppp = self.pixels_per_point;
+
+        match *event {
+            WindowEvent::HiDPIFactorChanged(factor) => {
+                self.pixels_per_point = factor;
+                false
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_pos = Pos2::new(position.0 as f32 / ppp, position.1 as f32 / ppp);
+                self.events.push(Event::PointerMoved(self.pointer_pos));
+                self.context.wants_pointer_input()
+            },
+            WindowEvent::CursorLeft { .. } => {
+                self.events.push(Event::PointerGone);
+                false
+            },
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    MouseButton::Left => PointerButton::Primary,
+                    MouseButton::Right => PointerButton::Secondary,
+                    MouseButton::Middle => PointerButton::Middle,
+                    MouseButton::Other(_) => return false
+                };
+
+                self.events.push(Event::PointerButton {
+                    pos : self.pointer_pos,
+                    button,
+                    pressed : state == ElementState::Pressed,
+                    modifiers : self.modifiers
+                });
+                self.context.wants_pointer_input()
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => egui::vec2(x, y) * SCROLL_LINE_POINTS,
+                    MouseScrollDelta::PixelDelta(x, y) => egui::vec2(x, y) / ppp
+                };
+
+                self.events.push(Event::Scroll(delta));
+                self.context.wants_pointer_input()
+            },
+            WindowEvent::ReceivedCharacter(character) => {
+                if character.is_control() {
+                    return false;
+                }
+
+                self.events.push(Event::Text(character.to_string()));
+                self.context.wants_keyboard_input()
+            },
+            WindowEvent::KeyboardInput { input, .. } => {
+                self.modifiers = translate_modifiers(input.modifiers);
+
+                if let Some(key) = input.virtual_keycode.and_then(translate_key) {
+                    self.events.push(Event::Key {
+                        key,
+                        pressed : input.state == ElementState::Pressed,
+                        repeat : false,
+                        modifiers : self.modifiers
+                    });
+                }
+
+                self.context.wants_keyboard_input()
+            },
+            _ => false
+        }
+    }
+
+    /// Runs the debugger UI for a frame, and draws it over the top of the window.
+    ///
+    /// * `window_size` - The size of the window, in physical pixels.
+    pub fn draw(&mut self, cpu : &mut CPU, controller : &mut DebugController,
+                window_size : (u32, u32)) {
+        if !self.visible {
+            return;
+        }
+
+        let ppp = self.pixels_per_point;
+        let screen_size = egui::vec2(window_size.0 as f32 / ppp, window_size.1 as f32 / ppp);
+
+        let mut input = RawInput::default();
+        input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, screen_size));
+        input.pixels_per_point = Some(ppp);
+        input.time = Some(self.start_time.elapsed().as_secs_f64());
+        input.modifiers = self.modifiers;
+        input.events = self.events.drain(..).collect();
+
+        let windows = &mut self.windows;
+        let output = self.context.run(input, |ctx| {
+            windows.show(ctx, cpu, controller);
+        });
+
+        let primitives = self.context.tessellate(output.shapes);
+        self.painter.paint(window_size, ppp, &primitives, &output.textures_delta);
+    }
+
+    /// Builds the overlay. The window's GL context must be current.
+    pub fn build(hidpi_factor : f32) -> DebugOverlay {
+        DebugOverlay {
+            visible : false,
+
+            context : egui::Context::default(),
+            painter : Painter::build(),
+            windows : DebuggerWindows::build(),
+
+            events : Vec::new(),
+            modifiers : Modifiers::default(),
+            pointer_pos : Pos2::ZERO,
+            pixels_per_point : hidpi_factor,
+            start_time : Instant::now()
+        }
+    }
+}
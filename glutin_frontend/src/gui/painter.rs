@@ -0,0 +1,268 @@
+/**
+ * painter.rs
+ *
+ * Renders egui's output using raw OpenGL, alongside the screen renderer.
+**/
+
+use std::collections::HashMap;
+use std::mem;
+use std::ptr;
+
+use gl;
+use gl::types::*;
+
+use egui::ClippedPrimitive;
+use egui::ImageData;
+use egui::TextureFilter;
+use egui::TextureId;
+use egui::TexturesDelta;
+use egui::epaint::ImageDelta;
+use egui::epaint::Primitive;
+use egui::epaint::Vertex;
+
+pub struct Painter {
+    program : GLuint,
+    vao : GLuint,
+    vbo : GLuint,
+    ebo : GLuint,
+    screen_size_uniform : GLint,
+    sampler_uniform : GLint,
+    textures : HashMap<TextureId, GLuint>
+}
+
+impl Painter {
+    /// Draws a frame of egui output over the top of whatever is in the window.
+    ///
+    /// * `window_size` - The size of the window, in physical pixels.
+    pub fn paint(&mut self, window_size : (u32, u32), pixels_per_point : f32,
+                 primitives : &[ClippedPrimitive], textures_delta : &TexturesDelta) {
+        for &(id, ref delta) in &textures_delta.set {
+            self.set_texture(id, delta);
+        }
+
+        let (width, height) = window_size;
+
+        unsafe {
+            gl::Viewport(0, 0, width as GLsizei, height as GLsizei);
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Enable(gl::BLEND);
+            // egui outputs premultiplied alpha
+            gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+
+            gl::UseProgram(self.program);
+            gl::Uniform2f(self.screen_size_uniform,
+                          width as f32 / pixels_per_point, height as f32 / pixels_per_point);
+            gl::Uniform1i(self.sampler_uniform, 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+
+            for primitive in primitives {
+                let mesh = match primitive.primitive {
+                    Primitive::Mesh(ref mesh) => mesh,
+                    // We don't register any custom callbacks
+                    Primitive::Callback(_) => continue
+                };
+
+                let texture = match self.textures.get(&mesh.texture_id) {
+                    Some(texture) => *texture,
+                    None => continue
+                };
+
+                // Clip rects are in points, from the top-left
+                let clip = primitive.clip_rect;
+                let min_x = (clip.min.x * pixels_per_point).round().max(0.0).min(width as f32);
+                let min_y = (clip.min.y * pixels_per_point).round().max(0.0).min(height as f32);
+                let max_x = (clip.max.x * pixels_per_point).round().max(min_x).min(width as f32);
+                let max_y = (clip.max.y * pixels_per_point).round().max(min_y).min(height as f32);
+
+                gl::Scissor(min_x as GLint, (height as f32 - max_y) as GLint,
+                            (max_x - min_x) as GLsizei, (max_y - min_y) as GLsizei);
+
+                gl::BufferData(gl::ARRAY_BUFFER,
+                               (mesh.vertices.len() * mem::size_of::<Vertex>()) as GLsizeiptr,
+                               mesh.vertices.as_ptr() as *const _, gl::STREAM_DRAW);
+                gl::BufferData(gl::ELEMENT_ARRAY_BUFFER,
+                               (mesh.indices.len() * mem::size_of::<u32>()) as GLsizeiptr,
+                               mesh.indices.as_ptr() as *const _, gl::STREAM_DRAW);
+
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::DrawElements(gl::TRIANGLES, mesh.indices.len() as GLsizei,
+                                 gl::UNSIGNED_INT, ptr::null());
+            }
+
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::Disable(gl::BLEND);
+        }
+
+        for id in &textures_delta.free {
+            self.free_texture(*id);
+        }
+    }
+
+    /// Creates or updates a texture which egui has requested.
+    fn set_texture(&mut self, id : TextureId, delta : &ImageDelta) {
+        let (size, pixels) : ([usize; 2], Vec<u8>) = match delta.image {
+            ImageData::Color(ref image) => {
+                let mut pixels = Vec::with_capacity(image.pixels.len() * 4);
+                for color in &image.pixels {
+                    pixels.extend_from_slice(&color.to_array());
+                }
+                (image.size, pixels)
+            },
+            ImageData::Font(ref image) => {
+                let mut pixels = Vec::with_capacity(image.pixels.len() * 4);
+                for color in image.srgba_pixels(None) {
+                    pixels.extend_from_slice(&color.to_array());
+                }
+                (image.size, pixels)
+            }
+        };
+
+        let filter = match delta.options.magnification {
+            TextureFilter::Linear => gl::LINEAR,
+            TextureFilter::Nearest => gl::NEAREST
+        };
+
+        unsafe {
+            let texture = match self.textures.get(&id) {
+                Some(texture) => *texture,
+                None => {
+                    let mut texture = 0;
+                    gl::GenTextures(1, &mut texture);
+                    texture
+                }
+            };
+            self.textures.insert(id, texture);
+
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as GLint);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+
+            match delta.pos {
+                // A partial update of an existing texture
+                Some([x, y]) => {
+                    gl::TexSubImage2D(gl::TEXTURE_2D, 0, x as GLint, y as GLint,
+                                      size[0] as GLsizei, size[1] as GLsizei,
+                                      gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_ptr() as *const _);
+                },
+                None => {
+                    gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as GLint,
+                                   size[0] as GLsizei, size[1] as GLsizei, 0,
+                                   gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_ptr() as *const _);
+                }
+            }
+        }
+    }
+
+    fn free_texture(&mut self, id : TextureId) {
+        if let Some(texture) = self.textures.remove(&id) {
+            unsafe {
+                gl::DeleteTextures(1, &texture);
+            }
+        }
+    }
+
+    /// Builds the painter. The window's GL context must be current.
+    pub fn build() -> Painter {
+        let mut vao = 0;
+        let mut vbo = 0;
+        let mut ebo = 0;
+
+        unsafe {
+            let vs = gl::CreateShader(gl::VERTEX_SHADER);
+            gl::ShaderSource(vs, 1, [VS_SRC.as_ptr() as *const _].as_ptr(), ptr::null());
+            gl::CompileShader(vs);
+
+            let fs = gl::CreateShader(gl::FRAGMENT_SHADER);
+            gl::ShaderSource(fs, 1, [FS_SRC.as_ptr() as *const _].as_ptr(), ptr::null());
+            gl::CompileShader(fs);
+
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vs);
+            gl::AttachShader(program, fs);
+            gl::BindFragDataLocation(program, 0, b"outColor\0".as_ptr() as *const _);
+            gl::LinkProgram(program);
+
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+
+            // Matches the layout of egui's Vertex: pos, uv, then a premultiplied sRGBA colour
+            let stride = mem::size_of::<Vertex>() as GLsizei;
+            let pos_attrib = gl::GetAttribLocation(program, b"a_pos\0".as_ptr() as *const _);
+            let tc_attrib = gl::GetAttribLocation(program, b"a_tc\0".as_ptr() as *const _);
+            let color_attrib = gl::GetAttribLocation(program, b"a_srgba\0".as_ptr() as *const _);
+            gl::VertexAttribPointer(pos_attrib as GLuint, 2, gl::FLOAT, gl::FALSE,
+                                    stride, ptr::null());
+            gl::VertexAttribPointer(tc_attrib as GLuint, 2, gl::FLOAT, gl::FALSE,
+                                    stride, (2 * mem::size_of::<f32>()) as *const _);
+            gl::VertexAttribPointer(color_attrib as GLuint, 4, gl::UNSIGNED_BYTE, gl::TRUE,
+                                    stride, (4 * mem::size_of::<f32>()) as *const _);
+            gl::EnableVertexAttribArray(pos_attrib as GLuint);
+            gl::EnableVertexAttribArray(tc_attrib as GLuint);
+            gl::EnableVertexAttribArray(color_attrib as GLuint);
+
+            gl::BindVertexArray(0);
+
+            return Painter {
+                program,
+                vao,
+                vbo,
+                ebo,
+                screen_size_uniform : gl::GetUniformLocation(program,
+                                                             b"u_screen_size\0".as_ptr() as *const _),
+                sampler_uniform : gl::GetUniformLocation(program, b"u_sampler\0".as_ptr() as *const _),
+                textures : HashMap::new()
+            }
+        }
+    }
+}
+
+const VS_SRC: &'static [u8] = b"
+    #version 150 core
+
+    uniform vec2 u_screen_size;
+
+    in vec2 a_pos;
+    in vec2 a_tc;
+    in vec4 a_srgba;
+
+    out vec4 v_rgba;
+    out vec2 v_tc;
+
+    void main()
+    {
+        gl_Position = vec4(2.0 * a_pos.x / u_screen_size.x - 1.0,
+                           1.0 - 2.0 * a_pos.y / u_screen_size.y,
+                           0.0, 1.0);
+        v_rgba = a_srgba;
+        v_tc = a_tc;
+    }
+\0";
+
+const FS_SRC: &'static [u8] = b"
+    #version 150 core
+
+    uniform sampler2D u_sampler;
+
+    in vec4 v_rgba;
+    in vec2 v_tc;
+
+    out vec4 outColor;
+
+    void main()
+    {
+        // Blended in gamma space - close enough for a debugger
+        outColor = v_rgba * texture(u_sampler, v_tc);
+    }
+\0";
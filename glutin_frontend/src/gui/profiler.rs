@@ -0,0 +1,92 @@
+/**
+ * gui/profiler.rs
+ *
+ * Shows where emulation time is going, per subsystem, refreshed every second.
+**/
+
+use std::time::Duration;
+use std::time::Instant;
+
+use egui;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::timing::reset_profiling;
+use oxidgb_core::debug::timing::start_profiling;
+use oxidgb_core::debug::timing::stop_profiling;
+use oxidgb_core::debug::timing::subsystem_timings;
+use oxidgb_core::debug::timing::SubsystemTiming;
+
+use clock;
+
+const REFRESH_INTERVAL : Duration = Duration::from_secs(1);
+
+pub struct ProfilerWindow {
+    pub show : bool,
+
+    /// What was measured over the last interval, and how long it was in nanoseconds.
+    timings : Vec<SubsystemTiming>,
+    measured : u64,
+    interval_start : Instant
+}
+
+impl ProfilerWindow {
+    /// Shows the profiler window, if enabled. Profiling only runs while it's open.
+    pub fn show(&mut self, ctx : &egui::Context, cpu : &mut CPU) {
+        if !self.show {
+            if cpu.mem.profiler.is_some() {
+                stop_profiling(cpu);
+            }
+            return;
+        }
+
+        if cpu.mem.profiler.is_none() {
+            start_profiling(cpu, clock::now);
+            self.timings.clear();
+            self.interval_start = Instant::now();
+        }
+
+        let elapsed = self.interval_start.elapsed();
+        if elapsed >= REFRESH_INTERVAL {
+            self.timings = subsystem_timings(cpu);
+            self.measured = clock::nanos(elapsed);
+            reset_profiling(cpu);
+            self.interval_start = Instant::now();
+        }
+
+        let mut open = true;
+        egui::Window::new("Profiler")
+            .open(&mut open)
+            .default_pos([520.0, 8.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.timings.is_empty() {
+                    ui.label("Measuring...");
+                    return;
+                }
+
+                let seconds = self.measured as f64 / 1_000_000_000.0;
+
+                egui::Grid::new("profiler_timings").striped(true).show(ui, |ui| {
+                    for timing in &self.timings {
+                        ui.monospace(timing.subsystem.name());
+                        ui.monospace(format!("{:>5.1}%",
+                                             timing.time as f64 / self.measured as f64 * 100.0));
+                        ui.monospace(format!("{:>10.0} {}/s", timing.count as f64 / seconds,
+                                             timing.subsystem.unit()));
+                        ui.end_row();
+                    }
+                });
+            });
+        self.show = open;
+    }
+
+    pub fn build() -> ProfilerWindow {
+        ProfilerWindow {
+            show : false,
+
+            timings : Vec::new(),
+            measured : 0,
+            interval_start : Instant::now()
+        }
+    }
+}
@@ -0,0 +1,156 @@
+/**
+ * gui/scanner.rs
+ *
+ * A RAM search dialog, for finding addresses to target with cheats.
+**/
+
+use egui;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::scan::Comparison;
+use oxidgb_core::debug::scan::MemoryScanner;
+
+/// Results are only listed once there are few enough to be useful.
+const MAX_LISTED_RESULTS : usize = 100;
+
+/// Parses a value as typed in, as decimal or as hex with a "$" or "0x" prefix.
+fn parse_value(value : &str) -> Option<u8> {
+    let value = value.trim();
+
+    if value.starts_with('$') {
+        u8::from_str_radix(&value[1 ..], 16).ok()
+    } else if value.starts_with("0x") {
+        u8::from_str_radix(&value[2 ..], 16).ok()
+    } else {
+        value.parse().ok()
+    }
+}
+
+pub struct RamSearch {
+    pub show : bool,
+
+    value_input : String,
+    scanner : MemoryScanner
+}
+
+impl RamSearch {
+    /// Shows the search window, if enabled.
+    pub fn show(&mut self, ctx : &egui::Context, cpu : &mut CPU) {
+        if !self.show {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("RAM search")
+            .open(&mut open)
+            .default_pos([520.0, 300.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.show_controls(ui, cpu);
+                ui.separator();
+                self.show_results(ui, cpu);
+            });
+        self.show = open;
+    }
+
+    fn show_controls(&mut self, ui : &mut egui::Ui, cpu : &CPU) {
+        let mut comparison = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Value:");
+            ui.add(egui::TextEdit::singleline(&mut self.value_input).desired_width(48.0));
+
+            if ui.button("Equals").clicked() {
+                match parse_value(&self.value_input) {
+                    Some(value) => comparison = Some(Comparison::Exact(value)),
+                    None => warn!("Invalid search value: {:?}", self.value_input)
+                }
+            }
+        });
+
+        // Relative comparisons need a snapshot to compare against first
+        let active = self.scanner.is_active();
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(active, egui::Button::new("Increased")).clicked() {
+                comparison = Some(Comparison::Increased);
+            }
+            if ui.add_enabled(active, egui::Button::new("Decreased")).clicked() {
+                comparison = Some(Comparison::Decreased);
+            }
+            if ui.add_enabled(active, egui::Button::new("Changed")).clicked() {
+                comparison = Some(Comparison::Changed);
+            }
+            if ui.add_enabled(active, egui::Button::new("Unchanged")).clicked() {
+                comparison = Some(Comparison::Unchanged);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button(if active { "Restart" } else { "Start" }).clicked() {
+                self.scanner.start(&cpu.mem);
+            }
+            if ui.add_enabled(active, egui::Button::new("Reset")).clicked() {
+                self.scanner.reset();
+            }
+        });
+
+        if let Some(comparison) = comparison {
+            self.scanner.filter(&cpu.mem, comparison);
+        }
+    }
+
+    fn show_results(&mut self, ui : &mut egui::Ui, cpu : &mut CPU) {
+        if !self.scanner.is_active() {
+            ui.label("Start a search, then narrow it down as the game runs.");
+            return;
+        }
+
+        let candidates = self.scanner.candidates();
+
+        ui.label(format!("{} candidate(s) after {} scan(s)", candidates.len(),
+                         self.scanner.scans()));
+
+        if candidates.len() > MAX_LISTED_RESULTS {
+            return;
+        }
+
+        let mut frozen = None;
+
+        egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+            egui::Grid::new("ram_search").num_columns(4).striped(true).show(ui, |ui| {
+                for candidate in candidates {
+                    let current = cpu.mem.debug_read(candidate.address);
+
+                    ui.monospace(format!("${:04X}", candidate.address));
+                    ui.monospace(format!("{:02X}", candidate.previous));
+                    ui.monospace(format!("{:02X}", current));
+
+                    if ui.small_button("Freeze").clicked() {
+                        frozen = Some((candidate.address, current));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        // Holds the value in place with a GameShark code, for this session
+        if let Some((address, value)) = frozen {
+            let code = format!("01{:02X}{:02X}{:02X}", value, address & 0xFF, address >> 8);
+
+            match cpu.mem.rom.cheats.add(&code, true) {
+                Ok(_) => info!("Added cheat {} (add it to the game's settings to keep it)", code),
+                Err(why) => warn!("Couldn't add cheat {}: {:?}", code, why)
+            }
+        }
+    }
+
+    pub fn build() -> RamSearch {
+        RamSearch {
+            show : false,
+
+            value_input : String::new(),
+            scanner : MemoryScanner::build()
+        }
+    }
+}
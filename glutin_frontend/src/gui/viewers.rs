@@ -0,0 +1,253 @@
+/**
+ * gui/viewers.rs
+ *
+ * Live views of VRAM/OAM: tiles, tilemaps, sprites and palettes.
+**/
+
+use egui;
+use egui::Color32;
+use egui::ColorImage;
+use egui::Pos2;
+use egui::Rect;
+use egui::Stroke;
+use egui::TextureHandle;
+use egui::TextureId;
+use egui::TextureOptions;
+
+use oxidgb_core::gpu::GPU;
+use oxidgb_core::debug::vram;
+use oxidgb_core::debug::vram::TileMap;
+
+/// How much images are scaled up by.
+const VIEWER_SCALE : f32 = 2.0;
+
+const SCROLL_COLOR : Color32 = Color32::from_rgb(230, 70, 70);
+const WINDOW_COLOR : Color32 = Color32::from_rgb(70, 130, 230);
+
+/// Uploads an RGB image into a texture, creating it if needed.
+fn update_texture(ctx : &egui::Context, slot : &mut Option<TextureHandle>, name : &str,
+                  size : [usize; 2], rgb : &[u8]) -> TextureId {
+    let image = ColorImage::from_rgb(size, rgb);
+
+    match *slot {
+        Some(ref mut texture) => texture.set(image, TextureOptions::NEAREST),
+        None => *slot = Some(ctx.load_texture(name, image, TextureOptions::NEAREST))
+    }
+
+    slot.as_ref().unwrap().id()
+}
+
+/// Outlines a area of a 256x256 tilemap, wrapping around the edges as the
+///  hardware does.
+fn outline_wrapped(painter : &egui::Painter, origin : Pos2, x : f32, y : f32,
+                   width : f32, height : f32, color : Color32) {
+    let size = vram::TILEMAP_SIZE as f32;
+    let map = Rect::from_min_size(Pos2::ZERO, egui::vec2(size, size));
+
+    for &offset_x in &[0.0, -size] {
+        for &offset_y in &[0.0, -size] {
+            let area = Rect::from_min_size(Pos2::new(x + offset_x, y + offset_y),
+                                           egui::vec2(width, height))
+                .intersect(map);
+
+            if area.width() <= 0.0 || area.height() <= 0.0 {
+                continue;
+            }
+
+            let area = Rect::from_min_max(origin + area.min.to_vec2() * VIEWER_SCALE,
+                                          origin + area.max.to_vec2() * VIEWER_SCALE);
+            painter.rect_stroke(area, 0.0, Stroke::new(1.0, color));
+        }
+    }
+}
+
+pub struct VramViewers {
+    pub show_tiles : bool,
+    pub show_tilemaps : bool,
+    pub show_sprites : bool,
+    pub show_palettes : bool,
+
+    selected_map : TileMap,
+
+    tiles_texture : Option<TextureHandle>,
+    tilemap_texture : Option<TextureHandle>,
+    sprites_texture : Option<TextureHandle>
+}
+
+impl VramViewers {
+    /// Shows any enabled viewer windows for a frame.
+    pub fn show(&mut self, ctx : &egui::Context, gpu : &GPU) {
+        if self.show_tiles {
+            self.tiles_window(ctx, gpu);
+        }
+
+        if self.show_tilemaps {
+            self.tilemaps_window(ctx, gpu);
+        }
+
+        if self.show_sprites {
+            self.sprites_window(ctx, gpu);
+        }
+
+        if self.show_palettes {
+            self.palettes_window(ctx, gpu);
+        }
+    }
+
+    fn tiles_window(&mut self, ctx : &egui::Context, gpu : &GPU) {
+        let texture = update_texture(ctx, &mut self.tiles_texture, "tiles",
+                                     [vram::TILE_SHEET_WIDTH, vram::TILE_SHEET_HEIGHT],
+                                     &vram::render_tiles(gpu));
+
+        let mut open = true;
+        egui::Window::new("Tiles")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("$8000 - $97FF, 16 tiles per row");
+                ui.image(texture, egui::vec2(vram::TILE_SHEET_WIDTH as f32,
+                                             vram::TILE_SHEET_HEIGHT as f32) * VIEWER_SCALE);
+            });
+        self.show_tiles = open;
+    }
+
+    fn tilemaps_window(&mut self, ctx : &egui::Context, gpu : &GPU) {
+        let size = vram::TILEMAP_SIZE as f32;
+        let texture = update_texture(ctx, &mut self.tilemap_texture, "tilemap",
+                                     [vram::TILEMAP_SIZE, vram::TILEMAP_SIZE],
+                                     &vram::render_tilemap(gpu, self.selected_map));
+
+        let mut open = true;
+        let selected_map = &mut self.selected_map;
+
+        egui::Window::new("Tile maps")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(selected_map, TileMap::Low, "$9800");
+                    ui.selectable_value(selected_map, TileMap::High, "$9C00");
+                });
+
+                let response = ui.image(texture, egui::vec2(size, size) * VIEWER_SCALE);
+                let painter = ui.painter_at(response.rect);
+                let origin = response.rect.min;
+
+                // The visible screen, if this map is the background
+                if *selected_map == TileMap::background(gpu) {
+                    outline_wrapped(&painter, origin, gpu.scx as f32, gpu.scy as f32,
+                                    160.0, 144.0, SCROLL_COLOR);
+                }
+
+                // The visible part of the window, if enabled and using this map
+                let window_enabled = gpu.lcdc >> 5 & 0x1 == 1;
+                let window_x = gpu.wx as f32 - 7.0;
+                let window_y = gpu.wy as f32;
+
+                if window_enabled && *selected_map == TileMap::window(gpu)
+                    && window_x < 160.0 && window_y < 144.0 {
+                    outline_wrapped(&painter, origin, 0.0, 0.0,
+                                    160.0 - window_x.max(0.0), 144.0 - window_y,
+                                    WINDOW_COLOR);
+                }
+
+                ui.horizontal(|ui| {
+                    ui.colored_label(SCROLL_COLOR, format!("Screen (SCX {}, SCY {})",
+                                                           gpu.scx, gpu.scy));
+                    ui.colored_label(WINDOW_COLOR, format!("Window (WX {}, WY {})",
+                                                           gpu.wx, gpu.wy));
+                });
+            });
+        self.show_tilemaps = open;
+    }
+
+    fn sprites_window(&mut self, ctx : &egui::Context, gpu : &GPU) {
+        let texture = update_texture(ctx, &mut self.sprites_texture, "sprites",
+                                     [vram::SPRITE_SHEET_WIDTH, vram::SPRITE_SHEET_HEIGHT],
+                                     &vram::render_sprites(gpu));
+        let height = vram::sprite_height(gpu) as i16;
+
+        let mut open = true;
+        egui::Window::new("Sprites")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("8x{} sprites, 8 per row", height));
+                ui.image(texture, egui::vec2(vram::SPRITE_SHEET_WIDTH as f32,
+                                             vram::SPRITE_SHEET_HEIGHT as f32) * VIEWER_SCALE);
+
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    egui::Grid::new("sprites").num_columns(6).striped(true).show(ui, |ui| {
+                        for heading in &["#", "X", "Y", "Tile", "Attr", "Flags"] {
+                            ui.strong(*heading);
+                        }
+                        ui.end_row();
+
+                        for sprite in vram::sprites(gpu) {
+                            let color = if sprite.is_visible(height) {
+                                ui.visuals().text_color()
+                            } else {
+                                ui.visuals().weak_text_color()
+                            };
+
+                            let flags = format!("{}{}{} OBP{}",
+                                                if sprite.x_flip { "X" } else { "-" },
+                                                if sprite.y_flip { "Y" } else { "-" },
+                                                if sprite.behind_background { "B" } else { "-" },
+                                                sprite.palette);
+
+                            ui.colored_label(color, format!("{}", sprite.index));
+                            ui.colored_label(color, format!("{}", sprite.x));
+                            ui.colored_label(color, format!("{}", sprite.y));
+                            ui.colored_label(color, format!("{:02X}", sprite.tile));
+                            ui.colored_label(color, format!("{:02X}", sprite.attributes));
+                            ui.colored_label(color, flags);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show_sprites = open;
+    }
+
+    fn palettes_window(&mut self, ctx : &egui::Context, gpu : &GPU) {
+        let mut open = true;
+        egui::Window::new("Palettes")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("palettes").num_columns(3).show(ui, |ui| {
+                    for &(name, register) in &[("BGP", gpu.bgp), ("OBP0", gpu.obp0),
+                                               ("OBP1", gpu.obp1)] {
+                        ui.monospace(name);
+                        ui.monospace(format!("{:02X}", register));
+
+                        ui.horizontal(|ui| {
+                            for color in &vram::palette_colors(gpu, register) {
+                                egui::widgets::color_picker::show_color(
+                                    ui, Color32::from_rgb(color[0], color[1], color[2]),
+                                    egui::vec2(16.0, 16.0));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+        self.show_palettes = open;
+    }
+
+    pub fn build() -> VramViewers {
+        VramViewers {
+            show_tiles : false,
+            show_tilemaps : false,
+            show_sprites : false,
+            show_palettes : false,
+
+            selected_map : TileMap::Low,
+
+            tiles_texture : None,
+            tilemap_texture : None,
+            sprites_texture : None
+        }
+    }
+}
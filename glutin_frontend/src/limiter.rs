@@ -0,0 +1,118 @@
+/**
+ * limiter.rs
+ *
+ * Paces emulation to the Gameboy's native refresh rate, with support for fast-forward.
+**/
+
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The Gameboy's native refresh rate: 4194304 Hz / 70224 cycles per frame.
+pub const GAMEBOY_FRAMERATE : f64 = 4194304.0 / 70224.0;
+
+/// How far behind the limiter may fall before it gives up catching up.
+const MAX_LAG_FRAMES : u32 = 4;
+
+/// How fast fast-forward is allowed to run.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum FastForwardCap {
+    #[serde(rename = "2x")]
+    Double,
+    #[serde(rename = "4x")]
+    Quadruple,
+    #[serde(rename = "uncapped")]
+    Uncapped
+}
+
+impl FastForwardCap {
+    /// Parses a cap as given on the command line ("2x", "4x", "uncapped").
+    pub fn parse(value : &str) -> Option<FastForwardCap> {
+        return match value.to_lowercase().as_str() {
+            "2" | "2x" => Some(FastForwardCap::Double),
+            "4" | "4x" => Some(FastForwardCap::Quadruple),
+            "0" | "uncapped" | "unlimited" => Some(FastForwardCap::Uncapped),
+            _ => None
+        }
+    }
+
+    /// Returns a human-readable name for this cap.
+    pub fn label(&self) -> &'static str {
+        return match *self {
+            FastForwardCap::Double => "2x",
+            FastForwardCap::Quadruple => "4x",
+            FastForwardCap::Uncapped => "uncapped"
+        }
+    }
+
+    /// Returns the speed multiplier for this cap, or None if uncapped.
+    pub fn multiplier(&self) -> Option<u32> {
+        return match *self {
+            FastForwardCap::Double => Some(2),
+            FastForwardCap::Quadruple => Some(4),
+            FastForwardCap::Uncapped => None
+        }
+    }
+}
+
+pub struct FrameLimiter {
+    frame_duration : Duration,
+    next_frame : Instant
+}
+
+impl FrameLimiter {
+    /// Blocks until the next frame should be emulated.
+    ///
+    /// * `speed` - The speed multiplier to run at, or None to not wait at all.
+    /// * `audio_queued` - If audio is enabled, how much audio is waiting to be played back.
+    /// * `audio_target` - How much queued audio to aim for when pacing against audio.
+    pub fn wait(&mut self, speed : Option<u32>, audio_queued : Option<Duration>,
+                audio_target : Duration) {
+        let speed = match speed {
+            Some(value) => value,
+            None => {
+                self.next_frame = Instant::now();
+                return;
+            }
+        };
+
+        // At normal speed, audio is the most accurate clock we have - only produce
+        //  frames as fast as the sound device consumes them.
+        if speed == 1 {
+            if let Some(queued) = audio_queued {
+                if queued > audio_target {
+                    thread::sleep(queued - audio_target);
+                }
+
+                self.next_frame = Instant::now();
+                return;
+            }
+        }
+
+        let frame_duration = self.frame_duration / speed;
+
+        self.next_frame += frame_duration;
+
+        let now = Instant::now();
+        if self.next_frame > now {
+            thread::sleep(self.next_frame - now);
+        } else if now - self.next_frame > frame_duration * MAX_LAG_FRAMES {
+            // We can't keep up - don't try to burst through the backlog
+            self.next_frame = now;
+        }
+    }
+
+    /// Resets the limiter, such as after being paused.
+    pub fn reset(&mut self) {
+        self.next_frame = Instant::now();
+    }
+
+    pub fn build() -> FrameLimiter {
+        let nanos = (1_000_000_000f64 / GAMEBOY_FRAMERATE) as u64;
+
+        return FrameLimiter {
+            frame_duration : Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32),
+            next_frame : Instant::now()
+        }
+    }
+}
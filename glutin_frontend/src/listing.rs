@@ -0,0 +1,77 @@
+/**
+ * listing.rs
+ *
+ * Exports a ROM as disassembled listings, one per bank, and manages the code
+ *  coverage maps which guide them.
+**/
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::coverage::CodeCoverage;
+use oxidgb_core::debug::export::bank_count;
+use oxidgb_core::debug::export::export_bank;
+
+use loader;
+
+/// Loads a coverage map for a ROM of `size` bytes. A missing file gives an
+///  empty map, so coverage can build up over several sessions.
+pub fn load_coverage(path : &Path, size : usize) -> Result<CodeCoverage, String> {
+    match fs::read(path) {
+        Ok(data) => Ok(CodeCoverage::from_bytes(data, size)),
+        Err(ref why) if why.kind() == ErrorKind::NotFound => Ok(CodeCoverage::build(size)),
+        Err(why) => Err(format!("couldn't read coverage {}: {}", path.display(), why))
+    }
+}
+
+/// Writes out any coverage recorded by a system, and stops recording.
+pub fn save_coverage(cpu : &mut CPU, path : &Path) {
+    let coverage = match cpu.mem.coverage.take() {
+        Some(coverage) => coverage,
+        None => return
+    };
+
+    match fs::write(path, coverage.as_bytes()) {
+        Ok(_) => info!("Wrote coverage of {} instructions to {}", coverage.count(), path.display()),
+        Err(why) => error!("couldn't write coverage {}: {}", path.display(), why)
+    }
+}
+
+/// Disassembles every bank of a ROM into `output`, as bank_XX.asm. With a
+///  coverage map, only executed bytes are listed as code.
+pub fn run(rom_path : &Path, output : &Path, coverage_path : Option<&Path>) -> Result<(), String> {
+    let rom = loader::read_rom(rom_path)?;
+
+    let coverage = match coverage_path {
+        Some(path) => {
+            if !path.exists() {
+                return Err(format!("no coverage at {}", path.display()));
+            }
+
+            Some(load_coverage(path, rom.len())?)
+        },
+        None => None
+    };
+
+    fs::create_dir_all(output)
+        .map_err(|why| format!("couldn't create {}: {}", output.display(), why))?;
+
+    let banks = bank_count(&rom);
+
+    for bank in 0 .. banks {
+        let path = output.join(format!("bank_{:02X}.asm", bank));
+
+        fs::write(&path, export_bank(&rom, bank, coverage.as_ref()))
+            .map_err(|why| format!("couldn't write {}: {}", path.display(), why))?;
+    }
+
+    match coverage {
+        Some(ref coverage) => println!("Wrote {} bank(s) to {}, guided by {} known instructions",
+                                       banks, output.display(), coverage.count()),
+        None => println!("Wrote {} bank(s) to {}", banks, output.display())
+    }
+
+    Ok(())
+}
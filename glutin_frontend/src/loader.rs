@@ -0,0 +1,158 @@
+/**
+ * loader.rs
+ *
+ * Loads ROMs (plain or zipped) from disk, and manages battery-backed saves.
+**/
+
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use zip::ZipArchive;
+
+use oxidgb_core::rom::GameROM;
+use oxidgb_core::mem::GBMemory;
+use oxidgb_core::cpu::CPU;
+
+/// File extensions which are treated as a raw ROM.
+pub const ROM_EXTENSIONS : [&'static str; 2] = ["gb", "gbc"];
+
+/// Returns if the path looks like something we can load.
+pub fn is_loadable(path : &Path) -> bool {
+    return match extension(path) {
+        Some(ext) => ext == "zip" || ROM_EXTENSIONS.contains(&ext.as_str()),
+        None => false
+    }
+}
+
+fn extension(path : &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+}
+
+/// Reads the first ROM found within a zip archive.
+fn read_zip(path : &Path) -> Result<Vec<u8>, String> {
+    let file = File::open(path)
+        .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+    let mut archive = ZipArchive::new(file)
+        .map_err(|why| format!("couldn't read archive {}: {}", path.display(), why))?;
+
+    for i in 0 .. archive.len() {
+        let mut entry = archive.by_index(i)
+            .map_err(|why| format!("couldn't read archive {}: {}", path.display(), why))?;
+
+        let is_rom = match extension(Path::new(entry.name())) {
+            Some(ext) => ROM_EXTENSIONS.contains(&ext.as_str()),
+            None => false
+        };
+
+        if !is_rom {
+            continue;
+        }
+
+        debug!("Loading {} from archive", entry.name());
+
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data)
+            .map_err(|why| format!("couldn't extract {}: {}", entry.name(), why))?;
+
+        return Ok(data);
+    }
+
+    Err(format!("{} does not contain a ROM", path.display()))
+}
+
+/// Reads a ROM's raw data from disk, extracting it if needed.
+pub fn read_rom(path : &Path) -> Result<Vec<u8>, String> {
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+
+    if extension(path) == Some("zip".to_string()) {
+        return read_zip(path);
+    }
+
+    let mut data = Vec::new();
+    let mut file = File::open(path)
+        .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+    file.read_to_end(&mut data)
+        .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+
+    Ok(data)
+}
+
+/// Returns where a game's battery save lives - alongside the ROM, unless a
+///  separate save directory is configured.
+pub fn save_path(rom_path : &Path, save_dir : Option<&Path>) -> PathBuf {
+    let local = rom_path.with_extension("sav");
+
+    match (save_dir, local.file_name()) {
+        (Some(dir), Some(name)) => dir.join(name),
+        _ => local
+    }
+}
+
+/// Loads a ROM from disk, and builds a fresh system around it, restoring
+///  any battery save.
+pub fn load_game(path : &Path, save_dir : Option<&Path>) -> Result<CPU, String> {
+    let data = read_rom(path)?;
+
+    let rom = GameROM::try_build(data)
+        .map_err(|why| format!("couldn't load {}: {:?}", path.display(), why))?;
+
+    let memory = GBMemory::build(rom);
+    let mut cpu = CPU::build(memory);
+
+    if cpu.mem.rom.cart_type.has_battery() {
+        let save = save_path(path, save_dir);
+
+        if save.exists() {
+            match fs::read(&save) {
+                Ok(data) => {
+                    if data.len() == cpu.mem.rom.cart_ram.len() {
+                        cpu.mem.rom.cart_ram.copy_from_slice(&data);
+                        info!("Loaded save from {}", save.display());
+                    } else {
+                        warn!("Ignoring save {}: expected {} bytes, got {}", save.display(),
+                              cpu.mem.rom.cart_ram.len(), data.len());
+                    }
+                },
+                Err(why) => warn!("couldn't read save {}: {}", save.display(), why)
+            }
+        }
+    }
+
+    Ok(cpu)
+}
+
+/// Writes a game's battery-backed RAM to disk, if it has any. Returns if
+///  a save was written.
+pub fn write_save(cpu : &CPU, rom_path : &Path, save_dir : Option<&Path>) -> bool {
+    if !cpu.mem.rom.cart_type.has_battery() || cpu.mem.rom.cart_ram.is_empty() {
+        return false;
+    }
+
+    if let Some(dir) = save_dir {
+        if let Err(why) = fs::create_dir_all(dir) {
+            error!("couldn't create {}: {}", dir.display(), why);
+            return false;
+        }
+    }
+
+    let save = save_path(rom_path, save_dir);
+
+    match fs::write(&save, &cpu.mem.rom.cart_ram) {
+        Ok(_) => {
+            info!("Wrote save to {}", save.display());
+            true
+        },
+        Err(why) => {
+            error!("couldn't write save {}: {}", save.display(), why);
+            false
+        }
+    }
+}
@@ -3,8 +3,11 @@ extern crate chrono;
 extern crate log;
 
 use std::io;
+use std::path::Path;
 
-pub fn setup_logging(is_verbose : bool) -> Result<(), fern::InitError> {
+/// Sets up logging to stdout, or to a file if one is specified (such as when the
+///  terminal is in use by the TUI debugger).
+pub fn setup_logging(is_verbose : bool, log_file : Option<&Path>) -> Result<(), fern::InitError> {
     let mut base_config = fern::Dispatch::new();
 
     if is_verbose {
@@ -13,7 +16,7 @@ pub fn setup_logging(is_verbose : bool) -> Result<(), fern::InitError> {
         base_config = base_config.level(log::LogLevelFilter::Info);
     }
 
-    let stdout_config = fern::Dispatch::new()
+    let output_config = fern::Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!("[{}] [{}:{}] [{}]: {}",
                                     chrono::Local::now().format("%H:%M:%S"),
@@ -21,10 +24,14 @@ pub fn setup_logging(is_verbose : bool) -> Result<(), fern::InitError> {
                                     record.location().line(),
                                     record.level(),
                                     message))
-        })
-        .chain(io::stdout());
+        });
 
-    base_config.chain(stdout_config).apply()?;
+    let output_config = match log_file {
+        Some(path) => output_config.chain(fern::log_file(path)?),
+        None => output_config.chain(io::stdout())
+    };
+
+    base_config.chain(output_config).apply()?;
 
     Ok(())
 }
@@ -12,22 +12,71 @@ extern crate clap;
 extern crate nfd;
 extern crate rustyline;
 
+// Terminal debugger
+#[cfg(feature = "tui")]
+#[macro_use]
+extern crate crossterm;
+#[cfg(feature = "tui")]
+extern crate ratatui;
+
 #[macro_use]
 extern crate log;
 
 extern crate oxidgb_core;
 
 extern crate rodio;
+extern crate zip;
+
+// Configuration
+extern crate dirs;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+
+// Save states
+extern crate bincode;
+
+// Graphical debugger
+#[cfg(feature = "egui")]
+extern crate egui;
 
 mod logging;
 mod debugger;
-
+mod audio;
+mod audit;
+mod bench;
+mod bindings;
+mod clock;
+mod config;
+mod font;
+mod limiter;
+mod listing;
+mod loader;
+mod osd;
+mod picker;
+mod renderer;
+mod stats;
+mod statediff;
+mod thumbnail;
+mod trace;
+mod video;
+
+#[cfg(feature = "tui")]
+mod tui;
+
+#[cfg(feature = "egui")]
+mod gui;
+
+#[cfg(feature = "rcheevos")]
+mod cheevos;
+
+use std::env;
 use std::ffi::CStr;
-use std::ptr;
-use std::mem;
 
 use clap::App;
 use clap::Arg;
+use clap::SubCommand;
 
 /*use sdl2::event::Event;
 use sdl2::pixels;
@@ -39,26 +88,119 @@ use glutin::GlContext;
 
 use nfd::Response;
 
-use std::{thread, time};
-use std::error::Error;
-use std::fs::File;
-use std::fs;
-use std::io::Read;
 use std::time::Duration;
+use std::time::Instant;
 use std::path::Path;
+use std::path::PathBuf;
 use std::process::exit;
 
-use oxidgb_core::input::GameboyButton;
-use oxidgb_core::rom::GameROM;
-use oxidgb_core::rom::get_rom_size;
-use oxidgb_core::mem::GBMemory;
 use oxidgb_core::cpu::CPU;
+use oxidgb_core::events::CoreEvent;
+use oxidgb_core::trace::Tracer;
+
+#[cfg(feature = "egui")]
+use oxidgb_core::debug::DebugController;
+
+use bindings::Action;
+use bindings::Bindings;
+use bindings::Hotkey;
+
+use config::Config;
+use config::GameSettings;
 
 use debugger::CommandLineDebugger;
 
-use rodio::Sink;
-use rodio::queue::queue;
-use rodio::buffer::SamplesBuffer;
+use audio::AudioOutput;
+
+use limiter::FastForwardCap;
+use limiter::FrameLimiter;
+
+use osd::OnScreenDisplay;
+
+use picker::PickerAction;
+use picker::RomPicker;
+
+use renderer::ScreenRenderer;
+
+use stats::StatsCounter;
+
+use trace::TraceWriter;
+
+#[cfg(feature = "egui")]
+use gui::DebugOverlay;
+
+use video::ScalingMode;
+
+/// How long audio is left to settle before its latency is measured.
+const LATENCY_SETTLE_SECS : u64 = 2;
+
+/// How long audio latency is averaged over.
+const LATENCY_WINDOW_SECS : u64 = 3;
+
+/// Colour of text drawn over the emulated frame.
+const OVERLAY_TEXT_COLOR : [u8; 3] = [255, 255, 255];
+
+/// Loads a game, applying its settings over the global ones.
+fn open_game(path : &Path, global_settings : &GameSettings,
+             save_dir : Option<&Path>) -> Result<CPU, String> {
+    let mut cpu = loader::load_game(path, save_dir)?;
+
+    let settings = config::game_settings(global_settings, &cpu.mem.rom);
+    config::apply_settings(&mut cpu, &settings);
+
+    Ok(cpu)
+}
+
+/// Loads the first game, giving up if it can't be loaded.
+fn open_game_or_exit(path : &Path, global_settings : &GameSettings,
+                     save_dir : Option<&Path>) -> CPU {
+    match open_game(path, global_settings, save_dir) {
+        Ok(cpu) => cpu,
+        Err(why) => {
+            error!("{}", why);
+            exit(2);
+        }
+    }
+}
+
+/// Saves cheats given on the command line to a game's overrides, then reapplies
+///  its settings so they take effect. Gives up if any code is invalid.
+fn add_cheats_or_exit(cpu : &mut CPU, global_settings : &GameSettings, codes : &[String]) {
+    if codes.is_empty() {
+        return;
+    }
+
+    for code in codes {
+        if let Err(why) = config::add_cheat(global_settings, &cpu.mem.rom, code) {
+            error!("{}", why);
+            exit(2);
+        }
+    }
+
+    let settings = config::game_settings(global_settings, &cpu.mem.rom);
+    config::apply_settings(cpu, &settings);
+}
+
+/// Asks the user for a ROM using a file dialog, giving up if none is chosen.
+fn browse_or_exit(config : &Config) -> PathBuf {
+    let default_path = config.directories.roms.as_ref()
+        .and_then(|path| path.to_str());
+
+    match nfd::open_file_dialog(Some("gb,gbc,zip"), default_path).unwrap() {
+        Response::Okay(file_path) => PathBuf::from(file_path),
+        _ => {
+            error!("No file selected.");
+            exit(2);
+        },
+    }
+}
+
+/// Remembers how a game looked when it was closed, for the ROM picker.
+fn save_thumbnail(cpu : &CPU) {
+    if let Some(path) = config::thumbnail_path(&config::game_id(&cpu.mem.rom)) {
+        thumbnail::save(&path, &cpu.mem.gpu.pixel_data);
+    }
+}
 
 fn main() {
     // Parse arguments
@@ -71,92 +213,376 @@ fn main() {
             .value_name("FILE")
             .help("Loads the specified ROM")
             .takes_value(true))
+        .arg(Arg::with_name("last")
+            .long("last")
+            .conflicts_with("load")
+            .help("Loads the most recently opened ROM"))
+        .arg(Arg::with_name("config")
+            .short("c")
+            .long("config")
+            .value_name("FILE")
+            .help("Uses the specified config file")
+            .takes_value(true))
         .arg(Arg::with_name("debug")
             .short("d")
             .long("debug")
-            .help("Enables debugging"))
+            .value_name("MODE")
+            .help("Enables debugging, using a terminal UI (tui, the default in tui builds) or a command line (cli)")
+            .possible_values(&["tui", "cli"])
+            .min_values(0)
+            .max_values(1)
+            .takes_value(true))
         .arg(Arg::with_name("verbose")
             .short("v")
             .long("verbose")
-            .help("Enables verbose logging"));
+            .help("Enables verbose logging"))
+        .arg(Arg::with_name("fast-forward")
+            .short("f")
+            .long("fast-forward")
+            .value_name("CAP")
+            .help("Sets the maximum fast-forward speed (2x, 4x or uncapped)")
+            .possible_values(&["2x", "4x", "uncapped"])
+            .takes_value(true))
+        .arg(Arg::with_name("no-audio")
+            .long("no-audio")
+            .help("Disables audio output"))
+        .arg(Arg::with_name("audio-device")
+            .long("audio-device")
+            .value_name("NAME")
+            .help("Sets the audio output device (see --list-audio-devices)")
+            .takes_value(true))
+        .arg(Arg::with_name("audio-latency")
+            .long("audio-latency")
+            .value_name("MS")
+            .help("Sets how much audio to keep queued, in milliseconds")
+            .takes_value(true))
+        .arg(Arg::with_name("list-audio-devices")
+            .long("list-audio-devices")
+            .help("Lists available audio output devices, then exits"))
+        .arg(Arg::with_name("cheat")
+            .long("cheat")
+            .value_name("CODE")
+            .help("Adds a Game Genie or GameShark code to the game's settings")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1))
+        .arg(Arg::with_name("coverage")
+            .long("coverage")
+            .value_name("FILE")
+            .help("Records which instructions run, adding to the coverage map in FILE")
+            .takes_value(true))
+        .arg(Arg::with_name("trace")
+            .long("trace")
+            .value_name("FILE")
+            .help("Records frames, interrupts, bank switches, DMA and audio to a Chrome trace")
+            .takes_value(true))
+        .arg(Arg::with_name("scaling")
+            .long("scaling")
+            .value_name("MODE")
+            .help("Sets how the screen is fit to the window")
+            .possible_values(&["integer", "aspect", "stretch"])
+            .takes_value(true))
+        .arg(Arg::with_name("fullscreen")
+            .long("fullscreen")
+            .help("Starts in fullscreen mode (toggle with Alt+Enter)"))
+        .subcommand(SubCommand::with_name("bench")
+            .about("Runs a ROM headless as fast as possible, and reports how long it took")
+            .arg(Arg::with_name("rom")
+                .value_name("ROM")
+                .help("The ROM to run")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("frames")
+                .long("frames")
+                .value_name("N")
+                .help("How many frames to run for")
+                .default_value("10000")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("audit")
+            .about("Runs a ROM twice with the same input, and reports the first frame where they differ")
+            .arg(Arg::with_name("rom")
+                .value_name("ROM")
+                .help("The ROM to run")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("frames")
+                .long("frames")
+                .value_name("N")
+                .help("How many frames to run for")
+                .default_value("3600")
+                .takes_value(true))
+            .arg(Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seeds the random input")
+                .default_value("1")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("disasm")
+            .about("Disassembles every bank of a ROM into RGBDS-style listings")
+            .arg(Arg::with_name("rom")
+                .value_name("ROM")
+                .help("The ROM to disassemble")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .value_name("DIR")
+                .help("Where to write the listings")
+                .default_value("disasm")
+                .takes_value(true))
+            .arg(Arg::with_name("coverage")
+                .long("coverage")
+                .value_name("FILE")
+                .help("A coverage map (from --coverage) separating code from data")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("statediff")
+            .about("Compares two save states field by field")
+            .arg(Arg::with_name("left")
+                .value_name("STATE")
+                .help("The first state")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("right")
+                .value_name("STATE")
+                .help("The second state")
+                .required(true)
+                .index(2)));
+
+    #[cfg(feature = "rcheevos")]
+    let app = app.arg(Arg::with_name("achievements")
+        .long("achievements")
+        .value_name("FILE")
+        .help("Evaluates achievements from a file (one ID:MEMADDR:Title per line)")
+        .takes_value(true));
 
     let args = app.get_matches();
 
-    let enable_debugging = args.is_present("debug");
     let enable_verbose = args.is_present("verbose");
 
-    // Set up logger
-    logging::setup_logging(enable_verbose).unwrap();
+    let (enable_debugging, enable_tui) = if args.is_present("debug") {
+        match args.value_of("debug") {
+            Some("cli") => (true, false),
+            _ if cfg!(feature = "tui") => (false, true),
+            // Without the terminal debugger, the command line one is the default
+            None => (true, false),
+            Some(_) => {
+                eprintln!("The terminal debugger isn't available - build with --features tui");
+                exit(1);
+            }
+        }
+    } else {
+        (false, false)
+    };
+
+    // Set up logger - the TUI owns the terminal, so log to a file instead
+    let log_file = if enable_tui {
+        Some(env::temp_dir().join("oxidgb.log"))
+    } else {
+        None
+    };
+
+    logging::setup_logging(enable_verbose, log_file.as_ref().map(|x| x.as_path())).unwrap();
 
     info!("Oxidgb v0.1");
 
-    let file = match args.value_of("load") {
-        Some(data) => data.to_string(),
-        None       => {
-            // Open a file dialog
-            match nfd::open_file_dialog(Some("gb"), None).unwrap() {
-                Response::Okay(file_path) => file_path,
-                _ => {
-                    error!("No file selected.");
-                    exit(2);
-                },
+    if let Some(bench_args) = args.subcommand_matches("bench") {
+        let frames = match bench_args.value_of("frames").unwrap().parse::<u32>() {
+            Ok(frames) => frames,
+            Err(_) => {
+                error!("Invalid frame count: {:?}", bench_args.value_of("frames").unwrap());
+                exit(2);
             }
+        };
+
+        if let Err(why) = bench::run(Path::new(bench_args.value_of("rom").unwrap()), frames) {
+            error!("{}", why);
+            exit(2);
         }
-    };
 
-    let rom_path = Path::new(&file);
-    if !rom_path.exists() {
-        error!("Specified file does not exist.");
-        exit(2);
+        return;
     }
 
-    // Load game ROM
+    if let Some(audit_args) = args.subcommand_matches("audit") {
+        let frames = match audit_args.value_of("frames").unwrap().parse::<u32>() {
+            Ok(frames) => frames,
+            Err(_) => {
+                error!("Invalid frame count: {:?}", audit_args.value_of("frames").unwrap());
+                exit(2);
+            }
+        };
 
-    let file_size = match fs::metadata(rom_path) {
-        Err(why) => panic!("couldn't read metadata of {}: {}", rom_path.display(),
-                           why.description()),
-        Ok(meta) => meta.len()
-    } as usize;
+        let seed = match audit_args.value_of("seed").unwrap().parse::<u32>() {
+            Ok(seed) => seed,
+            Err(_) => {
+                error!("Invalid seed: {:?}", audit_args.value_of("seed").unwrap());
+                exit(2);
+            }
+        };
 
-    let mut data = Vec::with_capacity(file_size);
+        if let Err(why) = audit::run(Path::new(audit_args.value_of("rom").unwrap()), frames, seed) {
+            error!("{}", why);
+            exit(1);
+        }
 
-    let mut file = match File::open(&rom_path) {
-        Err(why) => panic!("couldn't open {}: {}", rom_path.display(),
-                           why.description()),
-        Ok(file) => file,
-    };
+        return;
+    }
 
-    let read = file.read_to_end(&mut data).unwrap();
+    if let Some(disasm_args) = args.subcommand_matches("disasm") {
+        if let Err(why) = listing::run(Path::new(disasm_args.value_of("rom").unwrap()),
+                                       Path::new(disasm_args.value_of("output").unwrap()),
+                                       disasm_args.value_of("coverage").map(Path::new)) {
+            error!("{}", why);
+            exit(2);
+        }
 
-    assert_eq!(read, file_size);
-    let rom_size = get_rom_size(data[0x148]);
+        return;
+    }
 
-    if rom_size != file_size {
-        warn!("File size is not equal to what ROM declares!");
+    if let Some(diff_args) = args.subcommand_matches("statediff") {
+        match statediff::run(Path::new(diff_args.value_of("left").unwrap()),
+                             Path::new(diff_args.value_of("right").unwrap())) {
+            Ok(true) => return,
+            Ok(false) => exit(1),
+            Err(why) => {
+                error!("{}", why);
+                exit(2);
+            }
+        }
     }
 
-    let rom = GameROM::build(data);
+    if args.is_present("list-audio-devices") {
+        for name in audio::device_names() {
+            println!("{}", name);
+        }
+        return;
+    }
 
-    // Build memory
-    let memory = GBMemory::build(rom);
+    // Load config - command line arguments take precedence over it
+    let config_path = match args.value_of("config") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => config::config_path()
+    };
+
+    // A config which couldn't be loaded is never saved over, so it isn't lost
+    let (mut config, config_path) = match config_path {
+        Some(path) => match Config::load(&path) {
+            Ok(config) => (config, Some(path)),
+            Err(why) => {
+                warn!("{} - using defaults, and not saving settings", why);
+                (Config::default(), None)
+            }
+        },
+        None => {
+            warn!("Unable to find a config directory, using defaults");
+            (Config::default(), None)
+        }
+    };
+
+    let enable_audio = config.audio.enabled && !args.is_present("no-audio");
+    let audio_device = args.value_of("audio-device").map(|x| x.to_string())
+        .or_else(|| config.audio.device.clone());
+    let audio_latency = match args.value_of("audio-latency") {
+        Some(value) => match value.parse::<u64>() {
+            Ok(latency) => latency,
+            Err(_) => {
+                error!("Invalid audio latency: {:?}", value);
+                exit(2);
+            }
+        },
+        None => config.audio.latency_ms
+    };
+
+    if audio_latency < audio::MIN_LATENCY_MS {
+        warn!("Audio latency of {}ms is too low, using {}ms", audio_latency,
+              audio::MIN_LATENCY_MS);
+    }
+    let audio_latency = audio_latency.max(audio::MIN_LATENCY_MS);
+
+    let fast_forward_cap = match args.value_of("fast-forward") {
+        Some(value) => FastForwardCap::parse(value).unwrap(),
+        None => config.fast_forward
+    };
+    let scaling_mode = match args.value_of("scaling") {
+        Some(value) => ScalingMode::parse(value).unwrap(),
+        None => config.video.scaling
+    };
+    let mut fullscreen = config.video.fullscreen || args.is_present("fullscreen");
+
+    let window_scale = config.video.window_scale.max(1);
+    let save_dir = config.directories.saves.clone();
+    let save_dir = save_dir.as_ref().map(|x| x.as_path());
+    let global_settings = config.game.clone();
+    let bindings = Bindings::build(&config.input);
+    let new_cheats : Vec<String> = args.values_of("cheat")
+        .map(|codes| codes.map(String::from).collect())
+        .unwrap_or_default();
+
+    config.prune_recent_roms();
+
+    let initial_path = if args.is_present("last") {
+        match config.recent.first() {
+            Some(rom) => Some(rom.path.clone()),
+            None => {
+                error!("No ROMs have been opened recently.");
+                exit(2);
+            }
+        }
+    } else {
+        args.value_of("load").map(PathBuf::from)
+    };
 
     let mut debugger = CommandLineDebugger::build();
 
-    // Build CPU
-    let mut cpu = CPU::build(memory);
+    #[cfg(feature = "tui")]
+    if enable_tui {
+        // Runs headless - no window or audio
+        let rom_path = match initial_path {
+            Some(path) => path,
+            None => browse_or_exit(&config)
+        };
 
-    info!("Opening ROM: {}", cpu.mem.rom.name);
-    debug!("Mapper type: {:?}", cpu.mem.rom.cart_type);
+        let mut cpu = open_game_or_exit(&rom_path, &global_settings, save_dir);
+        add_cheats_or_exit(&mut cpu, &global_settings, &new_cheats);
+
+        config.add_recent_rom(&rom_path, &cpu.mem.rom);
+        if let Some(ref path) = config_path {
+            config.save(path);
+        }
+
+        info!("Opening ROM: {}", cpu.mem.rom.name);
+        debug!("Mapper type: {:?}", cpu.mem.rom.cart_type);
+
+        if let Err(why) = tui::run(&mut cpu) {
+            error!("Terminal debugger failed: {}", why);
+        }
+
+        loader::write_save(&cpu, &rom_path, save_dir);
+        save_thumbnail(&cpu);
+
+        if let Some(path) = log_file {
+            println!("Log written to {}", path.display());
+        }
+
+        return;
+    }
 
     let mut events_loop = glutin::EventsLoop::new();
     let window = glutin::WindowBuilder::new()
         .with_title("Oxidgb")
-        .with_dimensions(160 * 2, 144 * 2);
-    let context = glutin::ContextBuilder::new()
-        .with_vsync(true);
+        .with_dimensions(160 * window_scale, 144 * window_scale);
+    // The frame limiter paces us - vsync would fight it at 59.73Hz
+    let context = glutin::ContextBuilder::new();
     let gl_window = glutin::GlWindow::new(window,
                                           context, &events_loop).unwrap();
 
+    if fullscreen {
+        gl_window.set_fullscreen(Some(gl_window.get_current_monitor()));
+    }
+
+    let mut window_size = gl_window.get_inner_size()
+        .unwrap_or((160 * window_scale, 144 * window_scale));
+
     unsafe {
         gl_window.make_current().unwrap();
         gl::load_with(|symbol| gl_window.get_proc_address(symbol) as *const _);
@@ -171,79 +597,105 @@ fn main() {
 
     info!("OpenGL version: {}", version);
 
-    let mut tex = unsafe { mem::uninitialized() };
-    let mut ebo = unsafe { mem::uninitialized() };
+    let renderer = ScreenRenderer::build();
 
-    unsafe {
-        // Generate shaders
-        // Stolen from https://github.com/tomaka/glutin/blob/master/examples/support/mod.rs &
-        //             https://open.gl/content/code/c3_multitexture.txt
-        let vs = gl::CreateShader(gl::VERTEX_SHADER);
-        gl::ShaderSource(vs, 1, [VS_SRC.as_ptr() as *const _].as_ptr(), ptr::null());
-        gl::CompileShader(vs);
-
-        let fs = gl::CreateShader(gl::FRAGMENT_SHADER);
-        gl::ShaderSource(fs, 1, [FS_SRC.as_ptr() as *const _].as_ptr(), ptr::null());
-        gl::CompileShader(fs);
-
-        let program = gl::CreateProgram();
-        gl::AttachShader(program, vs);
-        gl::AttachShader(program, fs);
-        gl::BindFragDataLocation(program, 0, b"outColor\0".as_ptr() as *const _);
-        gl::LinkProgram(program);
-        gl::UseProgram(program);
-
-        gl::GenBuffers(1, &mut ebo);
-        gl::BindBuffer(gl::ARRAY_BUFFER, ebo);
-        gl::BufferData(gl::ARRAY_BUFFER,
-                       (ELEMENTS.len() * mem::size_of::<u32>()) as gl::types::GLsizeiptr,
-                       ELEMENTS.as_ptr() as *const _, gl::STATIC_DRAW);
-
-        let mut vb = mem::uninitialized();
-        gl::GenBuffers(1, &mut vb);
-        gl::BindBuffer(gl::ARRAY_BUFFER, vb);
-        gl::BufferData(gl::ARRAY_BUFFER,
-                      (VERTEX_DATA.len() * mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                      VERTEX_DATA.as_ptr() as *const _, gl::STATIC_DRAW);
-
-        let mut vao = mem::uninitialized();
-        gl::GenVertexArrays(1, &mut vao);
-        gl::BindVertexArray(vao);
-
-        let pos_attrib = gl::GetAttribLocation(program, b"position\0".as_ptr() as *const _);
-        let color_attrib = gl::GetAttribLocation(program, b"color\0".as_ptr() as *const _);
-        let tex_attrib = gl::GetAttribLocation(program, b"texcoord\0".as_ptr() as *const _);
-        gl::VertexAttribPointer(pos_attrib as gl::types::GLuint, 2, gl::FLOAT, 0,
-                               7 * mem::size_of::<f32>() as gl::types::GLsizei,
-                               ptr::null());
-        gl::VertexAttribPointer(color_attrib as gl::types::GLuint, 3, gl::FLOAT, 0,
-                               7 * mem::size_of::<f32>() as gl::types::GLsizei,
-                               (2 * mem::size_of::<f32>()) as *const () as *const _);
-        gl::VertexAttribPointer(tex_attrib as gl::types::GLuint, 2, gl::FLOAT, 0,
-                                7 * mem::size_of::<f32>() as gl::types::GLsizei,
-                                (5 * mem::size_of::<f32>()) as *const () as *const _);
-        gl::EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-        gl::EnableVertexAttribArray(color_attrib as gl::types::GLuint);
-        gl::EnableVertexAttribArray(tex_attrib as gl::types::GLuint);
-
-        // Generate texture (for us to dump into)
-        gl::GenTextures(1, &mut tex);
-        gl::ActiveTexture(gl::TEXTURE0);
-        gl::BindTexture(gl::TEXTURE_2D, tex);
-
-        gl::Uniform1i(gl::GetUniformLocation(program, b"tex\0".as_ptr() as *const _), 0);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as gl::types::GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as gl::types::GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as gl::types::GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as gl::types::GLint);
+    // Without a ROM to load, let the user pick from recent ones
+    let mut rom_path = match initial_path {
+        Some(path) => path,
+        None if !config.recent.is_empty() => {
+            let mut picker = RomPicker::build(&config.recent);
+
+            match picker.run(&mut events_loop, &gl_window, &renderer,
+                             &mut window_size, scaling_mode) {
+                Some(PickerAction::Load(path)) => path,
+                Some(PickerAction::Browse) => browse_or_exit(&config),
+                None => return
+            }
+        },
+        None => browse_or_exit(&config)
+    };
+
+    // Load game ROM
+    let mut cpu = open_game_or_exit(&rom_path, &global_settings, save_dir);
+    add_cheats_or_exit(&mut cpu, &global_settings, &new_cheats);
+
+    config.add_recent_rom(&rom_path, &cpu.mem.rom);
+    if let Some(ref path) = config_path {
+        config.save(path);
     }
 
+    info!("Opening ROM: {}", cpu.mem.rom.name);
+    debug!("Mapper type: {:?}", cpu.mem.rom.cart_type);
+
+    // The graphical debugger, toggled with F12
+    #[cfg(feature = "egui")]
+    let mut debug_controller = DebugController::build();
+    #[cfg(feature = "egui")]
+    let mut overlay = DebugOverlay::build(gl_window.hidpi_factor());
+    #[cfg(feature = "egui")]
+    let mut was_debug_paused = false;
+
     // Init audio
-    let device = rodio::default_output_device().unwrap();
-    let sink = Sink::new(&device);
-    let (audio_input, audio_output) = queue(true);
-    sink.append(audio_output);
-    sink.play();
+    let mut audio = if enable_audio {
+        Some(AudioOutput::build(audio_device.as_ref().map(|x| x.as_str()),
+                                config.audio.volume))
+    } else {
+        None
+    };
+
+    let mut osd = OnScreenDisplay::build();
+    osd.push(format!("Loaded {}", cpu.mem.rom.name));
+
+    // Achievements are only evaluated for the game loaded at startup
+    #[cfg(feature = "rcheevos")]
+    let achievement_events = match args.value_of("achievements") {
+        Some(path) => match cheevos::attach(&mut cpu, Path::new(path)) {
+            Ok(events) => Some(events),
+            Err(why) => {
+                error!("{}", why);
+                exit(2);
+            }
+        },
+        None => None
+    };
+
+    // Recorded events are written out every frame, so they don't build up
+    let mut trace_writer = match args.value_of("trace") {
+        Some(path) => match TraceWriter::create(Path::new(path)) {
+            Ok(writer) => {
+                info!("Tracing to {}", path);
+                cpu.mem.tracer = Some(Tracer::build(clock::now));
+                Some(writer)
+            },
+            Err(why) => {
+                error!("{}", why);
+                exit(2);
+            }
+        },
+        None => None
+    };
+
+    // Coverage is only recorded for the game loaded at startup
+    let coverage_path = args.value_of("coverage").map(PathBuf::from);
+    if let Some(ref path) = coverage_path {
+        match listing::load_coverage(path, cpu.mem.rom.data().len()) {
+            Ok(coverage) => cpu.mem.coverage = Some(coverage),
+            Err(why) => {
+                error!("{}", why);
+                exit(2);
+            }
+        }
+    }
+
+    let mut stats = StatsCounter::build();
+    gl_window.set_title(&stats::window_title(&cpu.mem.rom.name, false, None));
+
+    let audio_target = Duration::from_millis(audio_latency);
+
+    // Audio latency is measured once, after playback has settled
+    let mut latency_measure_at = Some(Instant::now() + Duration::from_secs(LATENCY_SETTLE_SECS));
+    let mut latency_report_at = None;
+    let mut limiter = FrameLimiter::build();
 
     let mut running = true;
 
@@ -251,164 +703,351 @@ fn main() {
     let mut gb_buttons = Vec::new();
     let mut fast_forward = false;
 
-    let mut last_synced = time::Instant::now();
+    // Pause/frame advance
+    let mut paused = false;
+    let mut frame_advance = false;
+
+    // Keys currently held down, so hotkeys can ignore key repeat
+    let mut held_keys = Vec::new();
+
+    // The emulated frame, plus anything drawn over it
+    let mut frame = cpu.mem.gpu.pixel_data.clone();
+
+    // Never present faster than the display could show it
+    let min_present_interval = Duration::from_millis(16);
+    let mut last_presented = Instant::now();
 
     while running {
-        let start_loop = time::Instant::now();
+        let mut fast_forward_changed = false;
+        let mut pause_changed = false;
+        let mut fullscreen_changed = false;
+        let mut dropped_file = None;
 
         events_loop.poll_events(|event| {
             match event {
-                glutin::Event::WindowEvent{ event, .. } => match event {
-                    glutin::WindowEvent::CloseRequested => running = false,
-                    glutin::WindowEvent::Resized(w, h) => gl_window.resize(w, h),
-                    glutin::WindowEvent::KeyboardInput { input, .. } => {
-                        match input.virtual_keycode {
-                            Some(key) => {
-                                let key = match key {
-                                    glutin::VirtualKeyCode::Up => GameboyButton::UP,
-                                    glutin::VirtualKeyCode::Down => GameboyButton::DOWN,
-                                    glutin::VirtualKeyCode::Left => GameboyButton::LEFT,
-                                    glutin::VirtualKeyCode::Right => GameboyButton::RIGHT,
-                                    glutin::VirtualKeyCode::X => GameboyButton::A,
-                                    glutin::VirtualKeyCode::Z => GameboyButton::B,
-                                    glutin::VirtualKeyCode::A => GameboyButton::SELECT,
-                                    glutin::VirtualKeyCode::S => GameboyButton::START,
-                                    glutin::VirtualKeyCode::Tab => {
-                                        match input.state {
-                                            glutin::ElementState::Pressed => {
-                                                fast_forward = true;
-                                            },
-                                            _ => {
-                                                fast_forward = false;
-                                            }
-                                        }
-                                        return;
-                                    },
-                                    _ => {
-                                        return;
+                glutin::Event::WindowEvent{ event, .. } => {
+                    #[cfg(feature = "egui")]
+                    {
+                        if overlay.handle_event(&event) {
+                            return;
+                        }
+                    }
+
+                    match event {
+                        glutin::WindowEvent::CloseRequested => running = false,
+                        glutin::WindowEvent::Resized(w, h) => {
+                            gl_window.resize(w, h);
+                            window_size = (w, h);
+                        },
+                        glutin::WindowEvent::DroppedFile(path) => dropped_file = Some(path),
+                        glutin::WindowEvent::KeyboardInput { input, .. } => {
+                            let key = match input.virtual_keycode {
+                                Some(key) => key,
+                                None => return
+                            };
+
+                            let pressed = input.state == glutin::ElementState::Pressed;
+                            let was_held = held_keys.contains(&key);
+
+                            if pressed && !was_held {
+                                held_keys.push(key);
+                            } else if !pressed {
+                                held_keys.retain(|x| *x != key);
+                            }
+
+                            #[cfg(feature = "egui")]
+                            {
+                                if key == glutin::VirtualKeyCode::F12 {
+                                    if pressed && !was_held {
+                                        overlay.visible = !overlay.visible;
                                     }
-                                };
+                                    return;
+                                }
+                            }
 
-                                match input.state {
-                                    glutin::ElementState::Pressed => {
-                                        if !gb_buttons.contains(&key) {
-                                            gb_buttons.push(key);
-                                        }
-                                    },
-                                    glutin::ElementState::Released => {
-                                        match gb_buttons.iter().position(|x| *x == key) {
-                                            Some(pos) => {
-                                                gb_buttons.remove(pos);
-                                            },
-                                            None => {}
+                            // Fullscreen isn't configurable, as it needs a modifier
+                            if key == glutin::VirtualKeyCode::Return && input.modifiers.alt {
+                                if pressed && !was_held {
+                                    fullscreen = !fullscreen;
+                                    fullscreen_changed = true;
+                                }
+                                return;
+                            }
+
+                            match bindings.lookup(key) {
+                                Some(&Action::Button(button)) => {
+                                    if pressed {
+                                        if !gb_buttons.contains(&button) {
+                                            gb_buttons.push(button);
                                         }
+                                    } else {
+                                        gb_buttons.retain(|x| *x != button);
                                     }
-                                }
-                            },
-                            None => {}
-                        }
-                    },
-                    _ => ()
+                                },
+                                Some(&Action::Hotkey(Hotkey::FastForward)) => {
+                                    if pressed != fast_forward {
+                                        fast_forward = pressed;
+                                        fast_forward_changed = true;
+                                    }
+                                },
+                                // Other hotkeys act once per press, ignoring key repeat
+                                Some(&Action::Hotkey(hotkey)) if pressed && !was_held => {
+                                    match hotkey {
+                                        Hotkey::Pause => {
+                                            paused = !paused;
+                                            pause_changed = true;
+                                        },
+                                        Hotkey::FrameAdvance => {
+                                            // Frame advance always leaves us paused
+                                            paused = true;
+                                            frame_advance = true;
+                                            pause_changed = true;
+                                        },
+                                        Hotkey::FastForward => {}
+                                    }
+                                },
+                                _ => {}
+                            }
+                        },
+                        _ => ()
+                    }
                 },
 
                 _ => ()
             }
         });
 
-        cpu.mem.set_input(&gb_buttons);
+        if let Some(path) = dropped_file {
+            if loader::is_loadable(&path) {
+                // Flush the current game before we lose it
+                if loader::write_save(&cpu, &rom_path, save_dir) {
+                    osd.push("Save RAM written");
+                }
+                save_thumbnail(&cpu);
+
+                if let Some(ref path) = coverage_path {
+                    listing::save_coverage(&mut cpu, path);
+                }
+
+                match open_game(&path, &global_settings, save_dir) {
+                    Ok(new_cpu) => {
+                        // Keep tracing across games
+                        let tracer = cpu.mem.tracer.take();
+                        cpu = new_cpu;
+                        cpu.mem.tracer = tracer;
+                        rom_path = path;
+
+                        config.add_recent_rom(&rom_path, &cpu.mem.rom);
+                        if let Some(ref path) = config_path {
+                            config.save(path);
+                        }
+
+                        info!("Opening ROM: {}", cpu.mem.rom.name);
+                        debug!("Mapper type: {:?}", cpu.mem.rom.cart_type);
+                        osd.push(format!("Loaded {}", cpu.mem.rom.name));
+
+                        gb_buttons.clear();
+                        limiter.reset();
 
-        if enable_debugging {
-            cpu.run(&mut Some(&mut debugger));
+                        stats.reset();
+                        gl_window.set_title(&stats::window_title(&cpu.mem.rom.name,
+                                                                 paused, None));
+                    },
+                    Err(why) => {
+                        error!("{}", why);
+                        osd.push("Unable to load ROM");
+                    }
+                }
+            } else {
+                warn!("Not a ROM: {}", path.display());
+                osd.push("Not a ROM");
+            }
+        }
+
+        if fullscreen_changed {
+            if fullscreen {
+                gl_window.set_fullscreen(Some(gl_window.get_current_monitor()));
+            } else {
+                gl_window.set_fullscreen(None);
+            }
+
+            // Remember this for next time
+            config.video.fullscreen = fullscreen;
+            if let Some(ref path) = config_path {
+                config.save(path);
+            }
+        }
+
+        if fast_forward_changed && fast_forward {
+            osd.push(format!("Fast-forward {}", fast_forward_cap.label()));
+        }
+
+        if fast_forward_changed || pause_changed || fullscreen_changed {
+            limiter.reset();
+        }
+
+        if pause_changed {
+            stats.reset();
+            gl_window.set_title(&stats::window_title(&cpu.mem.rom.name, paused, None));
+        }
+
+        let speed = if fast_forward {
+            fast_forward_cap.multiplier()
         } else {
-            cpu.run(&mut None);
+            Some(1)
+        };
+
+        let run_frame = !paused || frame_advance;
+        frame_advance = false;
+
+        let frame_completed = if run_frame {
+            cpu.mem.set_input(&gb_buttons);
+
+            if enable_debugging {
+                cpu.run(&mut Some(&mut debugger))
+            } else {
+                #[cfg(feature = "egui")]
+                let completed = cpu.run(&mut Some(&mut debug_controller));
+                #[cfg(not(feature = "egui"))]
+                let completed = cpu.run(&mut None);
+                completed
+            }
+        } else {
+            false
+        };
+
+        if frame_completed {
+            stats.frame_emulated();
+        }
+
+        for event in cpu.mem.events.take_events() {
+            match event {
+                // Games write to save RAM all the time, so this would be noise
+                CoreEvent::SaveRamWritten => {},
+                event => osd.push(event.to_string())
+            }
         }
 
-        let max_frame = Duration::from_millis(16);
+        // Nothing is connected to the link port, but test ROMs report over it
+        let serial = cpu.mem.take_serial_output();
+        if !serial.is_empty() {
+            debug!("Serial output: {:?}", String::from_utf8_lossy(&serial));
+        }
 
-        if !fast_forward || last_synced.elapsed() > max_frame {
-            if cpu.mem.gpu.is_enabled() {
-                unsafe {
-                    gl::Clear(gl::COLOR_BUFFER_BIT);
-
-                    gl::ActiveTexture(gl::TEXTURE0);
-                    gl::BindTexture(gl::TEXTURE_2D, tex);
-                    gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as gl::types::GLint, 160, 144, 0,
-                                   gl::RGB, gl::UNSIGNED_BYTE,
-                                   cpu.mem.gpu.pixel_data.as_ptr() as *const _);
-
-                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-                    gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT,
-                                     (0 * mem::size_of::<f32>()) as *const () as *const _);
+        #[cfg(feature = "rcheevos")]
+        {
+            if let Some(ref events) = achievement_events {
+                for title in events.try_iter() {
+                    osd.push(format!("Achievement unlocked: {}", title));
                 }
             }
+        }
 
-            gl_window.swap_buffers().unwrap();
-            last_synced = time::Instant::now();
+        // Hold off on audio while the graphical debugger has execution paused
+        #[cfg(feature = "egui")]
+        let debug_paused = debug_controller.is_paused();
+        #[cfg(not(feature = "egui"))]
+        let debug_paused = false;
+
+        #[cfg(feature = "egui")]
+        {
+            if was_debug_paused && !debug_paused {
+                limiter.reset();
+            }
+            was_debug_paused = debug_paused;
+        }
+
+        frame.copy_from_slice(&cpu.mem.gpu.pixel_data);
+
+        if paused {
+            font::draw_label(&mut frame, 4, 4, "PAUSED", OVERLAY_TEXT_COLOR);
         }
 
-        let elapsed = start_loop.elapsed();
-        if elapsed < max_frame && !fast_forward {
-            let sleep_time = max_frame - elapsed;
+        osd.draw(&mut frame);
+
+        if !fast_forward || last_presented.elapsed() > min_present_interval {
+            unsafe {
+                // Clearing ignores the viewport, so this also blanks the letterboxing
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+            }
+
+            if cpu.mem.gpu.is_enabled() {
+                let viewport = video::calculate_viewport(window_size.0, window_size.1,
+                                                         scaling_mode);
+                renderer.draw(&frame, viewport);
+            }
+
+            #[cfg(feature = "egui")]
+            overlay.draw(&mut cpu, &mut debug_controller, window_size);
+
+            gl_window.swap_buffers().unwrap();
+            last_presented = Instant::now();
+            stats.frame_presented();
+        }
 
-            thread::sleep(sleep_time);
+        if let Some(latest) = stats.poll() {
+            gl_window.set_title(&stats::window_title(&cpu.mem.rom.name, paused, Some(&latest)));
         }
 
         // Handle audio
-        let (samples, sample_count) = cpu.mem.sound.take_samples();
-        for i in 0 .. samples.len() {
-            samples[i] /= 100f32;
+        let audio_queued = match audio {
+            Some(ref mut output) if run_frame && !debug_paused => {
+                let (samples, sample_count) = cpu.mem.sound.take_samples();
+                output.submit(&samples[0 .. sample_count], speed);
+                cpu.mem.trace_instant("Audio submit", "audio", Some(("samples", sample_count as u32)));
+                Some(output.queued())
+            },
+            _ => None
+        };
+
+        if let Some(ref mut output) = audio {
+            let now = Instant::now();
+
+            if latency_measure_at.map_or(false, |x| now >= x) {
+                // Throw away anything measured while the queue was filling up
+                output.take_average_latency();
+                latency_measure_at = None;
+                latency_report_at = Some(now + Duration::from_secs(LATENCY_WINDOW_SECS));
+            } else if latency_report_at.map_or(false, |x| now >= x) {
+                latency_report_at = None;
+
+                if let Some(latency) = output.take_average_latency() {
+                    let millis = latency.as_secs() * 1000 + latency.subsec_millis() as u64;
+                    info!("Audio latency: {}ms queued (target {}ms)", millis, audio_latency);
+                    osd.push(format!("Audio latency {}ms", millis));
+                }
+            }
         }
-        let sample_buffer = SamplesBuffer::new(2,
-                                               oxidgb_core::sound::OUTPUT_FREQUENCY,
-                                               &samples[0 .. sample_count]);
-        audio_input.append(sample_buffer);
-    }
-}
 
-// OpenGL resources
-static VERTEX_DATA: [f32; 28] = [
-    // X    Y    R    G    B    U    V
-    -1.0,  1.0, 1.0, 1.0, 1.0, 0.0, 0.0, // Top-left
-     1.0,  1.0, 1.0, 1.0, 1.0, 1.0, 0.0, // Top-right
-     1.0, -1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // Bottom-right
-    -1.0, -1.0, 1.0, 1.0, 1.0, 0.0, 1.0  // Bottom-left
-];
-
-static ELEMENTS: [u32; 6] = [
-    0, 1, 2,
-    2, 3, 0
-];
-
-const VS_SRC: &'static [u8] = b"
-    #version 150 core
-
-    in vec2 position;
-    in vec3 color;
-    in vec2 texcoord;
-
-    out vec3 Color;
-    out vec2 Texcoord;
-
-    void main()
-    {
-        Color = color;
-        Texcoord = texcoord;
-        gl_Position = vec4(position, 0.0, 1.0);
-    }
-\0";
+        let trace_failed = match (trace_writer.as_mut(), cpu.mem.tracer.as_ref()) {
+            (Some(writer), Some(tracer)) => writer.write(&tracer.take_events()).err(),
+            _ => None
+        };
 
-const FS_SRC: &'static [u8] = b"
-    #version 150 core
+        if let Some(why) = trace_failed {
+            error!("{} - tracing stopped", why);
+            cpu.mem.tracer = None;
+            trace_writer = None;
+        }
 
-    in vec3 Color;
-    in vec2 Texcoord;
+        limiter.wait(speed, audio_queued, audio_target);
+    }
 
-    out vec4 outColor;
+    if let (Some(writer), Some(tracer)) = (trace_writer, cpu.mem.tracer.take()) {
+        if tracer.dropped() > 0 {
+            warn!("{} trace events were dropped", tracer.dropped());
+        }
 
-    uniform sampler2D tex;
+        match writer.finish() {
+            Ok(events) => info!("Wrote {} trace events", events),
+            Err(why) => error!("{}", why)
+        }
+    }
 
-    void main()
-    {
-        outColor = texture(tex, Texcoord) * vec4(Color, 1.0);
+    if let Some(ref path) = coverage_path {
+        listing::save_coverage(&mut cpu, path);
     }
-\0";
+
+    loader::write_save(&cpu, &rom_path, save_dir);
+    save_thumbnail(&cpu);
+}
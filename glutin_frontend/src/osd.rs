@@ -0,0 +1,74 @@
+/**
+ * osd.rs
+ *
+ * Transient on-screen messages, drawn over the emulated frame.
+**/
+
+use std::time::Duration;
+use std::time::Instant;
+
+use font;
+
+/// How long a message stays on screen.
+const MESSAGE_DURATION_MS : u64 = 2000;
+
+/// How many messages can be shown at once - older ones are dropped first.
+const MAX_MESSAGES : usize = 4;
+
+const TEXT_COLOR : [u8; 3] = [255, 255, 255];
+
+/// Pixels between the screen edge and messages.
+const MARGIN : usize = 4;
+
+/// Vertical distance between stacked messages.
+const LINE_HEIGHT : usize = font::GLYPH_HEIGHT + 4;
+
+const SCREEN_HEIGHT : usize = 144;
+
+struct Message {
+    text : String,
+    expires : Instant
+}
+
+pub struct OnScreenDisplay {
+    messages : Vec<Message>
+}
+
+impl OnScreenDisplay {
+    /// Shows a new message, replacing any identical message already on screen.
+    pub fn push<T : Into<String>>(&mut self, text : T) {
+        let text = text.into();
+
+        self.messages.retain(|x| x.text != text);
+
+        if self.messages.len() >= MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+
+        self.messages.push(Message {
+            text,
+            expires : Instant::now() + Duration::from_millis(MESSAGE_DURATION_MS)
+        });
+    }
+
+    /// Draws all active messages onto a frame, newest at the bottom.
+    pub fn draw(&mut self, frame : &mut [u8]) {
+        let now = Instant::now();
+        self.messages.retain(|x| x.expires > now);
+
+        let count = self.messages.len();
+
+        for (index, message) in self.messages.iter().enumerate() {
+            let y = SCREEN_HEIGHT - MARGIN - font::GLYPH_HEIGHT
+                - (count - 1 - index) * LINE_HEIGHT;
+
+            font::draw_label(frame, MARGIN, y, &message.text, TEXT_COLOR);
+        }
+    }
+
+    pub fn build() -> OnScreenDisplay {
+        OnScreenDisplay {
+            messages : Vec::new()
+        }
+    }
+}
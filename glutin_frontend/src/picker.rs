@@ -0,0 +1,190 @@
+/**
+ * picker.rs
+ *
+ * A simple startup screen for choosing between recently opened ROMs, showing
+ *  each game's title over how it looked when it was last closed.
+**/
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use gl;
+use glutin;
+use glutin::GlContext;
+use glutin::VirtualKeyCode;
+
+use oxidgb_core::gpu::PITCH;
+
+use config;
+use config::RecentRom;
+use font;
+use loader;
+use renderer::ScreenRenderer;
+use thumbnail;
+use video;
+use video::ScalingMode;
+use video::SCREEN_HEIGHT;
+use video::SCREEN_WIDTH;
+
+const TEXT_COLOR : [u8; 3] = [255, 255, 255];
+
+/// Shown behind games which don't have a thumbnail yet.
+const BACKGROUND_COLOR : [u8; 3] = [0x34, 0x68, 0x56];
+
+/// Pixels between the screen edge and text.
+const MARGIN : usize = 4;
+
+/// Vertical distance between lines of text.
+const LINE_HEIGHT : usize = font::GLYPH_HEIGHT + 4;
+
+/// How long to wait between redraws - nothing here needs to be smooth.
+const FRAME_INTERVAL_MS : u64 = 33;
+
+/// What the user chose to do.
+pub enum PickerAction {
+    Load(PathBuf),
+    /// Open a file dialog instead.
+    Browse
+}
+
+struct PickerEntry {
+    path : PathBuf,
+    title : String,
+    thumbnail : Option<Vec<u8>>
+}
+
+pub struct RomPicker {
+    entries : Vec<PickerEntry>,
+    selected : usize
+}
+
+impl RomPicker {
+    /// Handles a key press, returning what to do if a choice was made.
+    pub fn handle_key(&mut self, key : VirtualKeyCode) -> Option<PickerAction> {
+        let count = self.entries.len();
+
+        match key {
+            VirtualKeyCode::Left if count > 0 => self.selected = (self.selected + count - 1) % count,
+            VirtualKeyCode::Right if count > 0 => self.selected = (self.selected + 1) % count,
+            VirtualKeyCode::Return if count > 0 =>
+                return Some(PickerAction::Load(self.entries[self.selected].path.clone())),
+            VirtualKeyCode::O => return Some(PickerAction::Browse),
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Draws the picker into a RGB frame.
+    pub fn draw(&self, frame : &mut [u8]) {
+        let entry = self.entries.get(self.selected);
+
+        match entry.and_then(|x| x.thumbnail.as_ref()) {
+            Some(thumbnail) => frame.copy_from_slice(thumbnail),
+            None => {
+                for pixel in frame.chunks_mut(PITCH) {
+                    pixel.copy_from_slice(&BACKGROUND_COLOR);
+                }
+            }
+        }
+
+        // Leave room for the counter after the title
+        let max_chars = (SCREEN_WIDTH as usize - MARGIN * 2) / font::CHAR_ADVANCE - 6;
+
+        if let Some(entry) = entry {
+            let title : String = entry.title.chars().take(max_chars).collect();
+            font::draw_label(frame, MARGIN, MARGIN, &title, TEXT_COLOR);
+
+            let counter = format!("{}/{}", self.selected + 1, self.entries.len());
+            font::draw_label(frame, SCREEN_WIDTH as usize - MARGIN - font::text_width(&counter),
+                             MARGIN, &counter, TEXT_COLOR);
+        }
+
+        let bottom = SCREEN_HEIGHT as usize - MARGIN - font::GLYPH_HEIGHT;
+        font::draw_label(frame, MARGIN, bottom - LINE_HEIGHT, "LEFT/RIGHT: CHOOSE", TEXT_COLOR);
+        font::draw_label(frame, MARGIN, bottom, "ENTER: PLAY  O: OPEN FILE", TEXT_COLOR);
+    }
+
+    /// Shows the picker in a window until a choice is made. Returns None if the
+    ///  window was closed.
+    pub fn run(&mut self, events_loop : &mut glutin::EventsLoop, gl_window : &glutin::GlWindow,
+               renderer : &ScreenRenderer, window_size : &mut (u32, u32),
+               scaling_mode : ScalingMode) -> Option<PickerAction> {
+        let mut frame = vec![0u8; SCREEN_WIDTH as usize * SCREEN_HEIGHT as usize * PITCH];
+        let mut action = None;
+        let mut closed = false;
+
+        while action.is_none() && !closed {
+            events_loop.poll_events(|event| {
+                if let glutin::Event::WindowEvent { event, .. } = event {
+                    match event {
+                        glutin::WindowEvent::CloseRequested => closed = true,
+                        glutin::WindowEvent::Resized(w, h) => {
+                            gl_window.resize(w, h);
+                            *window_size = (w, h);
+                        },
+                        glutin::WindowEvent::DroppedFile(path) => {
+                            if loader::is_loadable(&path) {
+                                action = Some(PickerAction::Load(path));
+                            }
+                        },
+                        glutin::WindowEvent::KeyboardInput { input, .. } => {
+                            if input.state != glutin::ElementState::Pressed {
+                                return;
+                            }
+
+                            if let Some(key) = input.virtual_keycode {
+                                if let Some(choice) = self.handle_key(key) {
+                                    action = Some(choice);
+                                }
+                            }
+                        },
+                        _ => ()
+                    }
+                }
+            });
+
+            self.draw(&mut frame);
+
+            unsafe {
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+            }
+
+            renderer.draw(&frame, video::calculate_viewport(window_size.0, window_size.1,
+                                                           scaling_mode));
+            gl_window.swap_buffers().unwrap();
+
+            thread::sleep(Duration::from_millis(FRAME_INTERVAL_MS));
+        }
+
+        action
+    }
+
+    /// Builds a picker over the recently opened ROMs, loading their thumbnails.
+    pub fn build(recent : &[RecentRom]) -> RomPicker {
+        let entries = recent.iter().map(|rom| {
+            // Not every game fills in its title
+            let title = if rom.title.is_empty() {
+                rom.path.file_stem()
+                    .map(|x| x.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            } else {
+                rom.title.clone()
+            };
+
+            PickerEntry {
+                path : rom.path.clone(),
+                title,
+                thumbnail : config::thumbnail_path(&rom.id)
+                    .filter(|path| path.exists())
+                    .and_then(|path| thumbnail::load(&path))
+            }
+        }).collect();
+
+        RomPicker {
+            entries,
+            selected : 0
+        }
+    }
+}
@@ -0,0 +1,170 @@
+/**
+ * renderer.rs
+ *
+ * Draws the emulated screen to the window using OpenGL.
+**/
+
+use std::mem;
+use std::ptr;
+
+use gl;
+use gl::types::*;
+
+use video::Viewport;
+
+pub struct ScreenRenderer {
+    program : GLuint,
+    vao : GLuint,
+    ebo : GLuint,
+    tex : GLuint
+}
+
+impl ScreenRenderer {
+    /// Draws a 160x144 RGB frame into the specified area of the window.
+    pub fn draw(&self, frame : &[u8], viewport : Viewport) {
+        unsafe {
+            gl::Viewport(viewport.x, viewport.y, viewport.width, viewport.height);
+
+            // Other renderers may have changed state since we last drew
+            gl::UseProgram(self.program);
+            gl::BindVertexArray(self.vao);
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.tex);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as GLint, 160, 144, 0,
+                           gl::RGB, gl::UNSIGNED_BYTE,
+                           frame.as_ptr() as *const _);
+
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT,
+                             (0 * mem::size_of::<f32>()) as *const () as *const _);
+        }
+    }
+
+    /// Builds the renderer. The window's GL context must be current.
+    pub fn build() -> ScreenRenderer {
+        let mut tex = 0;
+        let mut ebo = 0;
+        let mut vao = 0;
+        let program;
+
+        unsafe {
+            // Generate shaders
+            // Stolen from https://github.com/tomaka/glutin/blob/master/examples/support/mod.rs &
+            //             https://open.gl/content/code/c3_multitexture.txt
+            let vs = gl::CreateShader(gl::VERTEX_SHADER);
+            gl::ShaderSource(vs, 1, [VS_SRC.as_ptr() as *const _].as_ptr(), ptr::null());
+            gl::CompileShader(vs);
+
+            let fs = gl::CreateShader(gl::FRAGMENT_SHADER);
+            gl::ShaderSource(fs, 1, [FS_SRC.as_ptr() as *const _].as_ptr(), ptr::null());
+            gl::CompileShader(fs);
+
+            program = gl::CreateProgram();
+            gl::AttachShader(program, vs);
+            gl::AttachShader(program, fs);
+            gl::BindFragDataLocation(program, 0, b"outColor\0".as_ptr() as *const _);
+            gl::LinkProgram(program);
+            gl::UseProgram(program);
+
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, ebo);
+            gl::BufferData(gl::ARRAY_BUFFER,
+                           (ELEMENTS.len() * mem::size_of::<u32>()) as GLsizeiptr,
+                           ELEMENTS.as_ptr() as *const _, gl::STATIC_DRAW);
+
+            let mut vb = 0;
+            gl::GenBuffers(1, &mut vb);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vb);
+            gl::BufferData(gl::ARRAY_BUFFER,
+                          (VERTEX_DATA.len() * mem::size_of::<f32>()) as GLsizeiptr,
+                          VERTEX_DATA.as_ptr() as *const _, gl::STATIC_DRAW);
+
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+
+            let pos_attrib = gl::GetAttribLocation(program, b"position\0".as_ptr() as *const _);
+            let color_attrib = gl::GetAttribLocation(program, b"color\0".as_ptr() as *const _);
+            let tex_attrib = gl::GetAttribLocation(program, b"texcoord\0".as_ptr() as *const _);
+            gl::VertexAttribPointer(pos_attrib as GLuint, 2, gl::FLOAT, 0,
+                                   7 * mem::size_of::<f32>() as GLsizei,
+                                   ptr::null());
+            gl::VertexAttribPointer(color_attrib as GLuint, 3, gl::FLOAT, 0,
+                                   7 * mem::size_of::<f32>() as GLsizei,
+                                   (2 * mem::size_of::<f32>()) as *const () as *const _);
+            gl::VertexAttribPointer(tex_attrib as GLuint, 2, gl::FLOAT, 0,
+                                    7 * mem::size_of::<f32>() as GLsizei,
+                                    (5 * mem::size_of::<f32>()) as *const () as *const _);
+            gl::EnableVertexAttribArray(pos_attrib as GLuint);
+            gl::EnableVertexAttribArray(color_attrib as GLuint);
+            gl::EnableVertexAttribArray(tex_attrib as GLuint);
+
+            // Generate texture (for us to dump into)
+            gl::GenTextures(1, &mut tex);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, tex);
+
+            gl::Uniform1i(gl::GetUniformLocation(program, b"tex\0".as_ptr() as *const _), 0);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+        }
+
+        return ScreenRenderer {
+            program,
+            vao,
+            ebo,
+            tex
+        }
+    }
+}
+
+// OpenGL resources
+static VERTEX_DATA: [f32; 28] = [
+    // X    Y    R    G    B    U    V
+    -1.0,  1.0, 1.0, 1.0, 1.0, 0.0, 0.0, // Top-left
+     1.0,  1.0, 1.0, 1.0, 1.0, 1.0, 0.0, // Top-right
+     1.0, -1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // Bottom-right
+    -1.0, -1.0, 1.0, 1.0, 1.0, 0.0, 1.0  // Bottom-left
+];
+
+static ELEMENTS: [u32; 6] = [
+    0, 1, 2,
+    2, 3, 0
+];
+
+const VS_SRC: &'static [u8] = b"
+    #version 150 core
+
+    in vec2 position;
+    in vec3 color;
+    in vec2 texcoord;
+
+    out vec3 Color;
+    out vec2 Texcoord;
+
+    void main()
+    {
+        Color = color;
+        Texcoord = texcoord;
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+\0";
+
+const FS_SRC: &'static [u8] = b"
+    #version 150 core
+
+    in vec3 Color;
+    in vec2 Texcoord;
+
+    out vec4 outColor;
+
+    uniform sampler2D tex;
+
+    void main()
+    {
+        outColor = texture(tex, Texcoord) * vec4(Color, 1.0);
+    }
+\0";
@@ -0,0 +1,43 @@
+/**
+ * statediff.rs
+ *
+ * Compares two save states (as written by the libretro core) field by field.
+**/
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use bincode;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::diff::diff_states;
+
+fn read_state(path : &Path) -> Result<CPU, String> {
+    let file = File::open(path)
+        .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+    // The libretro core saves whether a game is loaded along with it
+    let state : Option<CPU> = bincode::deserialize_from(&mut BufReader::new(file))
+        .map_err(|why| format!("couldn't read state {}: {}", path.display(), why))?;
+
+    state.ok_or_else(|| format!("{} doesn't contain a game", path.display()))
+}
+
+/// Prints every difference between two save states. Returns if they match.
+pub fn run(left : &Path, right : &Path) -> Result<bool, String> {
+    let left = read_state(left)?;
+    let right = read_state(right)?;
+
+    let differences = diff_states(&left, &right);
+
+    for difference in &differences {
+        println!("{}", difference);
+    }
+
+    if differences.is_empty() {
+        println!("States are identical");
+    }
+
+    Ok(differences.is_empty())
+}
@@ -0,0 +1,96 @@
+/**
+ * stats.rs
+ *
+ * Measures emulation speed and frontend framerate, for showing in the window title.
+**/
+
+use std::time::Duration;
+use std::time::Instant;
+
+use limiter::GAMEBOY_FRAMERATE;
+
+/// How often stats are updated.
+const SAMPLE_INTERVAL_MS : u64 = 1000;
+
+/// A snapshot of performance over the last interval.
+pub struct Stats {
+    /// Emulation speed relative to real hardware, where 100 is full speed.
+    pub speed_percent : f64,
+    /// Frames presented to the window per second.
+    pub fps : f64
+}
+
+pub struct StatsCounter {
+    emulated_frames : u32,
+    presented_frames : u32,
+    since : Instant
+}
+
+impl StatsCounter {
+    /// Records that a frame was emulated.
+    pub fn frame_emulated(&mut self) {
+        self.emulated_frames += 1;
+    }
+
+    /// Records that a frame was drawn to the window.
+    pub fn frame_presented(&mut self) {
+        self.presented_frames += 1;
+    }
+
+    /// Returns new stats once per interval, resetting the counts.
+    pub fn poll(&mut self) -> Option<Stats> {
+        let elapsed = self.since.elapsed();
+
+        if elapsed < Duration::from_millis(SAMPLE_INTERVAL_MS) {
+            return None;
+        }
+
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0;
+
+        let stats = Stats {
+            speed_percent : self.emulated_frames as f64 / seconds / GAMEBOY_FRAMERATE * 100.0,
+            fps : self.presented_frames as f64 / seconds
+        };
+
+        self.emulated_frames = 0;
+        self.presented_frames = 0;
+        self.since = Instant::now();
+
+        Some(stats)
+    }
+
+    /// Restarts counting, such as after a new game is loaded.
+    pub fn reset(&mut self) {
+        self.emulated_frames = 0;
+        self.presented_frames = 0;
+        self.since = Instant::now();
+    }
+
+    pub fn build() -> StatsCounter {
+        StatsCounter {
+            emulated_frames : 0,
+            presented_frames : 0,
+            since : Instant::now()
+        }
+    }
+}
+
+/// Builds the window title for a game.
+///
+/// * `stats` - The latest stats, if any have been measured yet.
+pub fn window_title(game_title : &str, paused : bool, stats : Option<&Stats>) -> String {
+    let mut title = String::from("Oxidgb");
+
+    if !game_title.is_empty() {
+        title.push_str(" - ");
+        title.push_str(game_title);
+    }
+
+    if paused {
+        title.push_str(" - Paused");
+    } else if let Some(stats) = stats {
+        title.push_str(&format!(" - {:.0}% - {:.0} FPS", stats.speed_percent, stats.fps));
+    }
+
+    title
+}
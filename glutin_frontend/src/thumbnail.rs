@@ -0,0 +1,47 @@
+/**
+ * thumbnail.rs
+ *
+ * Snapshots of the screen taken when a game is closed, shown by the ROM picker.
+ *  These are stored as binary PPMs, as they need no extra dependencies.
+**/
+
+use std::fs;
+use std::path::Path;
+
+use oxidgb_core::gpu::PITCH;
+
+use video::SCREEN_HEIGHT;
+use video::SCREEN_WIDTH;
+
+/// Writes a RGB frame to disk, creating its directory if needed.
+pub fn save(path : &Path, frame : &[u8]) {
+    if let Some(parent) = path.parent() {
+        if let Err(why) = fs::create_dir_all(parent) {
+            warn!("couldn't create {}: {}", parent.display(), why);
+            return;
+        }
+    }
+
+    let mut data = format!("P6\n{} {}\n255\n", SCREEN_WIDTH, SCREEN_HEIGHT).into_bytes();
+    data.extend_from_slice(frame);
+
+    match fs::write(path, data) {
+        Ok(_) => debug!("Wrote thumbnail to {}", path.display()),
+        Err(why) => warn!("couldn't write thumbnail {}: {}", path.display(), why)
+    }
+}
+
+/// Reads a RGB frame written by `save`. Anything else is rejected.
+pub fn load(path : &Path) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+
+    let header = format!("P6\n{} {}\n255\n", SCREEN_WIDTH, SCREEN_HEIGHT).into_bytes();
+    let size = SCREEN_WIDTH as usize * SCREEN_HEIGHT as usize * PITCH;
+
+    if data.len() != header.len() + size || !data.starts_with(&header) {
+        warn!("Ignoring invalid thumbnail {}", path.display());
+        return None;
+    }
+
+    Some(data[header.len() ..].to_vec())
+}
@@ -0,0 +1,96 @@
+/**
+ * trace.rs
+ *
+ * Writes events recorded by the core's tracer as a Chrome trace (JSON array
+ *  format), which chrome://tracing and Perfetto can open.
+**/
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use oxidgb_core::trace::TraceEvent;
+use oxidgb_core::trace::TraceKind;
+
+/// Escapes a string for use in JSON.
+fn escape(value : &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+
+    escaped
+}
+
+/// Streams trace events to a file as they are recorded.
+pub struct TraceWriter {
+    output : BufWriter<File>,
+    events : usize
+}
+
+impl TraceWriter {
+    /// Appends events to the trace.
+    pub fn write(&mut self, events : &[TraceEvent]) -> Result<(), String> {
+        for event in events {
+            let phase = match event.kind {
+                TraceKind::Begin => "B",
+                TraceKind::End => "E",
+                TraceKind::Instant => "i"
+            };
+
+            let mut json = format!("{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"{}\",\"ts\":{:.3},\
+                                    \"pid\":1,\"tid\":1",
+                                   escape(event.name), escape(event.category), phase,
+                                   event.time as f64 / 1000.0);
+
+            if event.kind == TraceKind::Instant {
+                // Draw instants across the whole thread, rather than the process
+                json.push_str(",\"s\":\"t\"");
+            }
+
+            if let Some((name, value)) = event.arg {
+                json.push_str(&format!(",\"args\":{{\"{}\":{}}}", escape(name), value));
+            }
+
+            json.push('}');
+
+            let separator = if self.events == 0 { "" } else { ",\n" };
+            write!(self.output, "{}{}", separator, json)
+                .map_err(|why| format!("couldn't write trace: {}", why))?;
+
+            self.events += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Closes off the trace, returning how many events it holds.
+    pub fn finish(mut self) -> Result<usize, String> {
+        writeln!(self.output, "\n]")
+            .and_then(|_| self.output.flush())
+            .map_err(|why| format!("couldn't write trace: {}", why))?;
+
+        Ok(self.events)
+    }
+
+    /// Creates a trace file, replacing any existing one.
+    pub fn create(path : &Path) -> Result<TraceWriter, String> {
+        let file = File::create(path)
+            .map_err(|why| format!("couldn't create {}: {}", path.display(), why))?;
+
+        let mut output = BufWriter::new(file);
+        writeln!(output, "[").map_err(|why| format!("couldn't write trace: {}", why))?;
+
+        Ok(TraceWriter {
+            output,
+            events : 0
+        })
+    }
+}
@@ -0,0 +1,374 @@
+/**
+ * tui.rs
+ *
+ * A terminal debugger, which runs the emulator headless. Useful over SSH, or on
+ *  machines without a GPU.
+**/
+
+use std::io;
+use std::io::Stdout;
+use std::time::Duration;
+
+use crossterm::event;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEventKind;
+use crossterm::terminal::EnterAlternateScreen;
+use crossterm::terminal::LeaveAlternateScreen;
+use crossterm::terminal::disable_raw_mode;
+use crossterm::terminal::enable_raw_mode;
+
+use ratatui::Frame;
+use ratatui::Terminal;
+use ratatui::backend::Backend;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Constraint;
+use ratatui::layout::Direction;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+
+use oxidgb_core::cpu::CPU;
+use oxidgb_core::debug::BreakReason;
+use oxidgb_core::debug::DebugController;
+use oxidgb_core::debug::disasm;
+use oxidgb_core::debug::parse_address;
+
+use limiter::FrameLimiter;
+
+/// How many rows of 16 bytes the memory view shows.
+const MEMORY_ROWS : u16 = 8;
+
+/// How long to wait for input while paused, so we don't spin.
+const PAUSED_POLL_MS : u64 = 50;
+
+const HELP : &'static str =
+    "c: continue  p: pause  s: step  o: step over  :: command  PgUp/PgDn: memory  q: quit";
+
+struct TerminalDebugger {
+    controller : DebugController,
+    memory_base : u16,
+    /// The command currently being typed, if any.
+    command : Option<String>,
+    /// Feedback from the last command.
+    status : String,
+    running : bool
+}
+
+impl TerminalDebugger {
+    /// Runs a command typed at the prompt.
+    fn run_command(&mut self, cpu : &CPU, line : &str) {
+        let mut args = line.split_whitespace();
+        let command = args.next();
+        let argument = args.next();
+
+        self.status = match (command, argument) {
+            (Some("b"), Some(value)) |
+            (Some("break"), Some(value)) => match parse_address(value) {
+                Some(address) => if self.controller.toggle_breakpoint(address) {
+                    format!("Added breakpoint at ${:04X}", address)
+                } else {
+                    format!("Removed breakpoint at ${:04X}", address)
+                },
+                None => format!("Invalid address: {}", value)
+            },
+            (Some("m"), Some(value)) |
+            (Some("mem"), Some(value)) => match parse_address(value) {
+                Some(address) => {
+                    self.memory_base = address & 0xFFF0;
+                    format!("Showing memory at ${:04X}", self.memory_base)
+                },
+                None => format!("Invalid address: {}", value)
+            },
+            (Some("s"), count) |
+            (Some("step"), count) => {
+                let count = count.and_then(|x| x.parse().ok()).unwrap_or(1);
+                self.controller.step(count);
+                format!("Stepping {} instruction(s)", count)
+            },
+            (Some("over"), _) => {
+                self.controller.step_over(cpu);
+                String::from("Stepping over")
+            },
+            (Some("q"), _) |
+            (Some("quit"), _) => {
+                self.running = false;
+                String::new()
+            },
+            (None, _) => String::new(),
+            _ => String::from("Commands: break ADDR, mem ADDR, step [N], over, quit")
+        };
+    }
+
+    /// Handles a single key press.
+    fn handle_key(&mut self, cpu : &CPU, key : KeyCode) {
+        if let Some(mut command) = self.command.take() {
+            match key {
+                KeyCode::Enter => self.run_command(cpu, &command),
+                KeyCode::Esc => {},
+                KeyCode::Backspace => {
+                    command.pop();
+                    self.command = Some(command);
+                },
+                KeyCode::Char(character) => {
+                    command.push(character);
+                    self.command = Some(command);
+                },
+                _ => self.command = Some(command)
+            }
+
+            return;
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.running = false,
+            KeyCode::Char('c') | KeyCode::F(5) => self.controller.resume(),
+            KeyCode::Char('p') => self.controller.pause(),
+            KeyCode::Char('s') | KeyCode::F(7) => {
+                if self.controller.is_paused() {
+                    self.controller.step(1);
+                }
+            },
+            KeyCode::Char('o') | KeyCode::F(8) => {
+                if self.controller.is_paused() {
+                    self.controller.step_over(cpu);
+                }
+            },
+            KeyCode::Char(':') => self.command = Some(String::new()),
+            KeyCode::PageUp => {
+                self.memory_base = self.memory_base.wrapping_sub(MEMORY_ROWS * 16);
+            },
+            KeyCode::PageDown => {
+                self.memory_base = self.memory_base.wrapping_add(MEMORY_ROWS * 16);
+            },
+            _ => {}
+        }
+    }
+
+    fn draw<B : Backend>(&self, frame : &mut Frame<B>, cpu : &CPU) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(8),
+                          Constraint::Length(MEMORY_ROWS + 2),
+                          Constraint::Length(1)].as_ref())
+            .split(frame.size());
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(40), Constraint::Length(30)].as_ref())
+            .split(rows[0]);
+
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(10), Constraint::Min(3)].as_ref())
+            .split(columns[1]);
+
+        self.draw_disassembly(frame, columns[0], cpu);
+        self.draw_registers(frame, side[0], cpu);
+        self.draw_breakpoints(frame, side[1]);
+        self.draw_memory(frame, rows[1], cpu);
+
+        let status = match self.command {
+            Some(ref command) => format!(":{}", command),
+            None if !self.status.is_empty() => self.status.clone(),
+            None => String::from(HELP)
+        };
+
+        frame.render_widget(Paragraph::new(status), rows[2]);
+    }
+
+    fn draw_disassembly<B : Backend>(&self, frame : &mut Frame<B>, area : Rect, cpu : &CPU) {
+        let pc = cpu.regs.pc;
+
+        // Borders take a line each side
+        let visible = area.height.saturating_sub(2) as usize;
+        let before = visible / 3;
+        let after = visible.saturating_sub(before + 1);
+
+        let instructions = disasm::disassemble_around(|x| cpu.mem.debug_read(x), pc,
+                                                      before, after);
+
+        let lines : Vec<Line> = instructions.iter().map(|instruction| {
+            let breakpoint = self.controller.has_breakpoint(instruction.address);
+
+            let mut bytes = String::new();
+            for i in 0 .. 3 {
+                if i < instruction.length as usize {
+                    bytes.push_str(&format!("{:02X} ", instruction.bytes[i]));
+                } else {
+                    bytes.push_str("   ");
+                }
+            }
+
+            let style = if instruction.address == pc {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else if breakpoint {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+
+            Line::from(vec![
+                Span::styled(if breakpoint { "* " } else { "  " }, Style::default().fg(Color::Red)),
+                Span::styled(format!("{:04X}  {} {}", instruction.address, bytes,
+                                     instruction.text), style)
+            ])
+        }).collect();
+
+        let title = if !self.controller.is_paused() {
+            String::from("Disassembly (running)")
+        } else {
+            match self.controller.last_break() {
+                Some(BreakReason::Breakpoint(address)) =>
+                    format!("Disassembly (breakpoint ${:04X})", address),
+                _ => String::from("Disassembly (paused)")
+            }
+        };
+
+        frame.render_widget(Paragraph::new(lines)
+                                .block(Block::default().title(title).borders(Borders::ALL)),
+                            area);
+    }
+
+    fn draw_registers<B : Backend>(&self, frame : &mut Frame<B>, area : Rect, cpu : &CPU) {
+        let regs = &cpu.regs;
+        let flag = |set : bool, name : &'static str| if set { name } else { "-" };
+
+        let lines = vec![
+            Line::from(format!("AF {:04X}   BC {:04X}", regs.get_af(), regs.get_bc())),
+            Line::from(format!("DE {:04X}   HL {:04X}", regs.get_de(), regs.get_hl())),
+            Line::from(format!("SP {:04X}   PC {:04X}", regs.sp, regs.pc)),
+            Line::from(format!("IE {:02X}     IF {:02X}", cpu.mem.interrupt_reg,
+                               cpu.mem.ioregs.iflag)),
+            Line::from(format!("LCDC {:02X}   LY {:02X}", cpu.mem.gpu.lcdc,
+                               cpu.mem.gpu.current_line)),
+            Line::from(format!("Flags {}{}{}{}", flag(regs.get_flag_z(), "Z"),
+                               flag(regs.get_flag_n(), "N"), flag(regs.get_flag_h(), "H"),
+                               flag(regs.get_flag_c(), "C"))),
+            Line::from(format!("IME {}  {}", if cpu.interrupts_enabled { "on" } else { "off" },
+                               if cpu.halted { "HALT" } else if cpu.stopped { "STOP" } else { "" }))
+        ];
+
+        frame.render_widget(Paragraph::new(lines)
+                                .block(Block::default().title("Registers").borders(Borders::ALL)),
+                            area);
+    }
+
+    fn draw_breakpoints<B : Backend>(&self, frame : &mut Frame<B>, area : Rect) {
+        let lines : Vec<Line> = self.controller.breakpoints.iter()
+            .map(|address| Line::from(format!("${:04X}", address)))
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines)
+                                .block(Block::default().title("Breakpoints").borders(Borders::ALL)),
+                            area);
+    }
+
+    fn draw_memory<B : Backend>(&self, frame : &mut Frame<B>, area : Rect, cpu : &CPU) {
+        let mut lines = Vec::new();
+
+        for row in 0 .. MEMORY_ROWS {
+            let address = self.memory_base.wrapping_add(row * 16);
+
+            let mut hex = String::new();
+            let mut ascii = String::new();
+            for i in 0 .. 16 {
+                let value = cpu.mem.debug_read(address.wrapping_add(i));
+                hex.push_str(&format!("{:02X} ", value));
+                ascii.push(if value >= 0x20 && value < 0x7F { value as char } else { '.' });
+            }
+
+            lines.push(Line::from(format!("{:04X}  {} {}", address, hex, ascii)));
+        }
+
+        frame.render_widget(Paragraph::new(lines)
+                                .block(Block::default().title("Memory").borders(Borders::ALL)),
+                            area);
+    }
+}
+
+fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    enable_raw_mode()?;
+
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+
+    Terminal::new(CrosstermBackend::new(stdout))
+}
+
+fn restore_terminal(terminal : &mut Terminal<CrosstermBackend<Stdout>>) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()
+}
+
+/// Runs the terminal debugger until the user quits. Execution starts paused.
+pub fn run(cpu : &mut CPU) -> io::Result<()> {
+    let mut terminal = setup_terminal()?;
+
+    let mut debugger = TerminalDebugger {
+        controller : DebugController::build(),
+        memory_base : 0xC000,
+        command : None,
+        status : String::new(),
+        running : true
+    };
+    debugger.controller.pause();
+
+    let mut limiter = FrameLimiter::build();
+    let mut was_paused = true;
+
+    let result = (|| -> io::Result<()> {
+        while debugger.running {
+            terminal.draw(|frame| debugger.draw(frame, cpu))?;
+
+            let paused = debugger.controller.is_paused();
+            let timeout = if paused {
+                Duration::from_millis(PAUSED_POLL_MS)
+            } else {
+                Duration::from_millis(0)
+            };
+
+            if event::poll(timeout)? {
+                // Handle everything which is waiting before drawing again
+                loop {
+                    if let Event::Key(key) = event::read()? {
+                        if key.kind == KeyEventKind::Press {
+                            debugger.handle_key(cpu, key.code);
+                        }
+                    }
+
+                    if !event::poll(Duration::from_millis(0))? {
+                        break;
+                    }
+                }
+            }
+
+            if debugger.controller.is_paused() {
+                was_paused = true;
+                continue;
+            }
+
+            if was_paused {
+                limiter.reset();
+                was_paused = false;
+            }
+
+            cpu.run(&mut Some(&mut debugger.controller));
+            limiter.wait(Some(1), None, Duration::from_millis(0));
+        }
+
+        Ok(())
+    })();
+
+    restore_terminal(&mut terminal)?;
+
+    result
+}
@@ -0,0 +1,77 @@
+/**
+ * video.rs
+ *
+ * Works out where on the window the emulated screen should be drawn.
+**/
+
+pub const SCREEN_WIDTH : u32 = 160;
+pub const SCREEN_HEIGHT : u32 = 144;
+
+/// How the emulated screen is fit into the window.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScalingMode {
+    /// The largest whole-number multiple of the screen that fits, letterboxed.
+    Integer,
+    /// The largest 10:9 area that fits, letterboxed.
+    Aspect,
+    /// Fills the entire window, ignoring aspect ratio.
+    Stretch
+}
+
+impl ScalingMode {
+    /// Parses a scaling mode as given on the command line.
+    pub fn parse(value : &str) -> Option<ScalingMode> {
+        return match value.to_lowercase().as_str() {
+            "integer" => Some(ScalingMode::Integer),
+            "aspect" => Some(ScalingMode::Aspect),
+            "stretch" => Some(ScalingMode::Stretch),
+            _ => None
+        }
+    }
+}
+
+/// A area of the window, in pixels, as expected by glViewport.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    pub x : i32,
+    pub y : i32,
+    pub width : i32,
+    pub height : i32
+}
+
+/// Returns the largest area with the screen's aspect ratio which fits the window.
+fn fit_aspect(window_width : u32, window_height : u32) -> (u32, u32) {
+    // Compare width / height ratios without going through floats
+    if window_width * SCREEN_HEIGHT > window_height * SCREEN_WIDTH {
+        (window_height * SCREEN_WIDTH / SCREEN_HEIGHT, window_height)
+    } else {
+        (window_width, window_width * SCREEN_HEIGHT / SCREEN_WIDTH)
+    }
+}
+
+/// Calculates where the screen should be drawn within a window of the given size.
+pub fn calculate_viewport(window_width : u32, window_height : u32,
+                          mode : ScalingMode) -> Viewport {
+    let (width, height) = match mode {
+        ScalingMode::Integer => {
+            let scale = (window_width / SCREEN_WIDTH).min(window_height / SCREEN_HEIGHT);
+
+            if scale == 0 {
+                // Window is smaller than the screen - do the best we can
+                fit_aspect(window_width, window_height)
+            } else {
+                (SCREEN_WIDTH * scale, SCREEN_HEIGHT * scale)
+            }
+        }
+        ScalingMode::Aspect => fit_aspect(window_width, window_height),
+        ScalingMode::Stretch => (window_width, window_height)
+    };
+
+    return Viewport {
+        x : ((window_width - width) / 2) as i32,
+        y : ((window_height - height) / 2) as i32,
+        width : width as i32,
+        height : height as i32
+    }
+}
@@ -210,6 +210,10 @@ pub trait Core: Default {
     fn on_unserialize( &mut self, _buffer : &[u8] ) -> bool {
         false
     }
+    fn on_cheat_reset( &mut self ) {
+    }
+    fn on_cheat_set( &mut self, _index: usize, _is_enabled: bool, _code: &str ) {
+    }
 }
 
 static mut ENVIRONMENT_CALLBACK: Option< libretro_sys::EnvironmentFn > = None;
@@ -449,9 +453,18 @@ impl< B: Core > Retro< B > {
     }
 
     pub fn on_cheat_reset( &mut self ) {
+        self.core.on_cheat_reset()
     }
 
-    pub fn on_cheat_set( &mut self, _index: libc::c_uint, _is_enabled: bool, _code: *const libc::c_char ) {
+    pub fn on_cheat_set( &mut self, index: libc::c_uint, is_enabled: bool, code: *const libc::c_char ) {
+        if code.is_null() {
+            return;
+        }
+
+        let code = unsafe { CStr::from_ptr( code ) };
+        if let Ok( code ) = code.to_str() {
+            self.core.on_cheat_set( index as usize, is_enabled, code )
+        }
     }
 
     pub fn on_unload_game( &mut self ) {
@@ -20,6 +20,7 @@ mod logging;
 
 use libretro_backend::*;
 
+use oxidgb_core::events::CoreEvent;
 use oxidgb_core::input::GameboyButton;
 use oxidgb_core::rom::GameROM;
 use oxidgb_core::mem::GBMemory;
@@ -39,7 +40,9 @@ use std::error::Error;
 struct OxidgbEmulator {
     game_data: Option<GameData>,
     cpu: Option<CPU>,
-    serialized_size: usize
+    serialized_size: usize,
+    /// Cheats as set by the frontend - each may hold several codes joined by "+".
+    cheats: Vec<Option<(bool, String)>>
 }
 
 impl OxidgbEmulator {
@@ -47,7 +50,27 @@ impl OxidgbEmulator {
         OxidgbEmulator {
             game_data: None,
             cpu: None,
-            serialized_size: 0
+            serialized_size: 0,
+            cheats: Vec::new()
+        }
+    }
+
+    /// Rebuilds the running game's cheats from those set by the frontend.
+    fn apply_cheats(&mut self) {
+        let cpu = match self.cpu {
+            Some(ref mut cpu) => cpu,
+            None => return
+        };
+
+        let manager = &mut cpu.mem.rom.cheats;
+        manager.clear();
+
+        for &(enabled, ref codes) in self.cheats.iter().filter_map(|x| x.as_ref()) {
+            for code in codes.split('+') {
+                if let Err(why) = manager.add(code, enabled) {
+                    warn!("Ignoring invalid cheat {:?}: {:?}", code, why);
+                }
+            }
         }
     }
 }
@@ -115,6 +138,7 @@ impl libretro_backend::Core for OxidgbEmulator {
 
         self.game_data = Some(game_data);
         self.cpu = Some(cpu);
+        self.apply_cheats();
 
         self.serialized_size = bincode::serialized_size(&self.cpu).unwrap() as _;
 
@@ -162,6 +186,22 @@ impl libretro_backend::Core for OxidgbEmulator {
         cpu.mem.set_input(&gb_buttons);
         cpu.run(&mut None);
 
+        // Warnings were logged by the core as they happened, and save RAM writes
+        //  happen all the time - anything else is worth a mention
+        for event in cpu.mem.events.take_events() {
+            match event {
+                CoreEvent::SaveRamWritten |
+                CoreEvent::UnsupportedFeature(_) |
+                CoreEvent::AccuracyWarning(_) => {},
+                event => info!("{}", event)
+            }
+        }
+
+        let serial = cpu.mem.take_serial_output();
+        if !serial.is_empty() {
+            debug!("Serial output: {:?}", String::from_utf8_lossy(&serial));
+        }
+
         let mut pixel_data = [0 as u8; 160 * 144 * 4];
 
         {
@@ -215,14 +255,54 @@ impl libretro_backend::Core for OxidgbEmulator {
     }
 
     fn on_unserialize(&mut self, buffer : &[u8]) -> bool {
-        self.cpu = match bincode::deserialize_from(&mut Cursor::new(buffer)) {
+        let mut cpu : Option<CPU> = match bincode::deserialize_from(&mut Cursor::new(buffer)) {
             Ok(v) => v,
-            Err(v) => panic!("Error while unserializing: {:?}", v)
+            Err(v) => {
+                error!("Error while unserializing: {:?}", v);
+                return false;
+            }
         };
 
+        // Save states don't include the ROM, so the running game's is moved over
+        match (cpu.as_mut(), self.cpu.as_mut()) {
+            (Some(new_cpu), Some(old_cpu)) => {
+                if new_cpu.mem.rom.name != old_cpu.mem.rom.name {
+                    error!("Save state is for {}, not {}", new_cpu.mem.rom.name,
+                           old_cpu.mem.rom.name);
+                    return false;
+                }
+
+                new_cpu.mem.rom.attach_data(old_cpu.mem.rom.take_data());
+            },
+            (Some(new_cpu), None) => {
+                error!("Save state is for {}, but no game is running", new_cpu.mem.rom.name);
+                return false;
+            },
+            (None, _) => {}
+        }
+
+        self.cpu = cpu;
+
+        // Cheats aren't part of save states
+        self.apply_cheats();
+
         true
     }
 
+    fn on_cheat_reset(&mut self) {
+        self.cheats.clear();
+        self.apply_cheats();
+    }
+
+    fn on_cheat_set(&mut self, index: usize, is_enabled: bool, code: &str) {
+        while self.cheats.len() <= index {
+            self.cheats.push(None);
+        }
+
+        self.cheats[index] = Some((is_enabled, code.to_owned()));
+        self.apply_cheats();
+    }
+
     fn save_memory(&mut self) -> Option<&mut [u8]> {
         match &mut self.cpu {
             &mut Some(ref mut v) => Some(&mut v.mem.rom.cart_ram),